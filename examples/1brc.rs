@@ -1,5 +1,4 @@
 use sandl::*;
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
@@ -14,7 +13,7 @@ struct ChunkArgs {
 }
 
 // Partial statistics for one station in one chunk
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default)]
 struct StationStats {
     min: f64,
     max: f64,
@@ -49,40 +48,51 @@ impl StationStats {
     fn mean(&self) -> f64 {
         self.sum / self.count as f64
     }
-}
 
-json_wrapper!(PartialResults, HashMap<String, StationStats>);
+    fn to_value(&self) -> Value {
+        value!({
+            "min": (self.min),
+            "max": (self.max),
+            "sum": (self.sum),
+            "count": (self.count)
+        })
+    }
+
+    // Any numeric variant works for the float fields, so results from other producers
+    // (whole-degree temperatures stored as ints, say) merge the same way
+    fn from_value(value: &Value) -> Option<Self> {
+        let float = |field: &str| value.get(field).and_then(Value::as_number);
+        Some(Self {
+            min: float("min")?,
+            max: float("max")?,
+            sum: float("sum")?,
+            count: value.get("count").and_then(Value::as_integer)? as u64,
+        })
+    }
+}
 
 // Core processing function for each chunk
 fn process_chunk(args: &ChunkArgs, _ctx: &Context) -> Result<Value> {
-    let mut file =
-        File::open(&args.file_path).map_err(|e| execution_error!("Failed to open file: {}", e))?;
+    let mut file = File::open(&args.file_path)?;
 
     // Seek to our chunk's start
-    file.seek(SeekFrom::Start(args.start_byte))
-        .map_err(|e| execution_error!("Failed to seek: {}", e))?;
+    file.seek(SeekFrom::Start(args.start_byte))?;
 
     let chunk_size = (args.end_byte - args.start_byte) as usize;
     let mut limited = file.take(chunk_size as u64);
     let mut reader = BufReader::new(&mut limited);
 
     let mut buffer = String::new();
-    let mut stats = PartialResults::new();
+    let mut stats: HashMap<String, StationStats> = HashMap::new();
 
     // If we're not at the file start, skip the first partial line
     if args.start_byte > 0 {
-        reader
-            .read_line(&mut buffer)
-            .map_err(|e| execution_error!("Failed to read line: {}", e))?;
+        reader.read_line(&mut buffer)?;
         buffer.clear();
     }
 
     // Process all lines in this chunk
-    while reader
-        .read_line(&mut buffer)
-        .map_err(|e| execution_error!("Failed to read line: {}", e))?
-        > 0
-    {
+    while reader.read_line(&mut buffer)? > 0 {
         let line = buffer.trim();
 
         if let Some((station, temp_str)) = line.split_once(';') {
@@ -97,7 +107,12 @@ fn process_chunk(args: &ChunkArgs, _ctx: &Context) -> Result<Value> {
         buffer.clear();
     }
 
-    Ok(stats.to_value())
+    Ok(Value::Object(
+        stats
+            .into_iter()
+            .map(|(station, stats)| (station, stats.to_value()))
+            .collect(),
+    ))
 }
 
 fn main() -> Result<()> {
@@ -173,13 +188,14 @@ fn main() -> Result<()> {
 
     for (_slice_name, slice_result) in results.iter() {
         if let Ok(slice_results) = slice_result {
-            let key = ("process".to_string(), "chunk".to_string());
-            if let Some(Ok(value)) = slice_results.method_results.get(&key) {
-                let partial = PartialResults::from_value(value)?;
-
-                for (station, stats) in partial.into_inner() {
+            let key = method_key("process", "chunk");
+            if let Some(Ok(Value::Object(partial))) = slice_results.method_results.get(&key) {
+                for (station, stats) in partial {
+                    let Some(stats) = StationStats::from_value(stats) else {
+                        continue;
+                    };
                     final_stats
-                        .entry(station)
+                        .entry(station.clone())
                         .and_modify(|s| s.merge(&stats))
                         .or_insert(stats);
                 }
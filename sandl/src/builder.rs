@@ -1,15 +1,19 @@
 use crate::*;
+use std::collections::HashMap;
 use std::marker::PhantomData;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 
 pub struct LayerBuilder {
     name: String,
     methods: Vec<MethodBuilder>,
+    max_concurrency: Option<usize>,
 }
 
 pub struct MethodBuilder {
     name: String,
     default_args: Value,
+    context_default: Option<String>,
+    description: Option<String>,
     func: Option<LayerMethodFn>,
 }
 
@@ -18,6 +22,7 @@ impl Layer {
         LayerBuilder {
             name: name.into(),
             methods: Vec::new(),
+            max_concurrency: None,
         }
     }
 }
@@ -28,20 +33,37 @@ impl LayerBuilder {
         MethodBuilderArgsStep {
             layer_builder: self,
             method_name,
+            description: None,
         }
     }
 
+    /// At most `n` calls into this layer's methods run at once, across all slices and
+    /// waves. Extra calls wait for a free permit; retries release theirs while backing off.
+    pub fn max_concurrency(mut self, n: usize) -> Self {
+        self.max_concurrency = Some(n.max(1));
+        self
+    }
+
     pub fn build(self) -> Layer {
         let mut layer = Layer {
             name: self.name,
             methods_to_defaults: std::collections::HashMap::new(),
             binds: std::collections::HashMap::new(),
+            context_defaults: std::collections::HashMap::new(),
+            descriptions: std::collections::HashMap::new(),
+            max_concurrency: self.max_concurrency,
         };
 
         for method in self.methods {
             layer
                 .methods_to_defaults
                 .insert(method.name.clone(), method.default_args);
+            if let Some(key) = method.context_default {
+                layer.context_defaults.insert(method.name.clone(), key);
+            }
+            if let Some(description) = method.description {
+                layer.descriptions.insert(method.name.clone(), description);
+            }
             if let Some(func) = method.func {
                 layer.binds.insert(method.name, func);
             }
@@ -54,21 +76,34 @@ impl LayerBuilder {
 pub struct MethodBuilderArgsStep {
     layer_builder: LayerBuilder,
     method_name: String,
+    description: Option<String>,
 }
 
 pub struct MethodBuilderBindStep<A> {
     layer_builder: LayerBuilder,
     method_name: String,
     default_args: Value,
+    context_default: Option<String>,
+    description: Option<String>,
+    cache_key_fields: Option<Vec<String>>,
     _phantom: PhantomData<A>,
 }
 
 impl MethodBuilderArgsStep {
+    /// Human-readable summary of the method, listed by `Engine::describe`.
+    pub fn describe(mut self, text: impl Into<String>) -> Self {
+        self.description = Some(text.into());
+        self
+    }
+
     pub fn args_with_default<A: FromValue + ToValue>(self, default: A) -> MethodBuilderBindStep<A> {
         MethodBuilderBindStep {
             layer_builder: self.layer_builder,
             method_name: self.method_name,
             default_args: default.to_value(),
+            context_default: None,
+            description: self.description,
+            cache_key_fields: None,
             _phantom: PhantomData,
         }
     }
@@ -78,12 +113,29 @@ impl MethodBuilderArgsStep {
             layer_builder: self.layer_builder,
             method_name: self.method_name,
             default_args: Value::Null,
+            context_default: None,
+            description: self.description,
+            cache_key_fields: None,
             _phantom: PhantomData,
         }
     }
 }
 
 impl<A: FromValue + ToValue + 'static> MethodBuilderBindStep<A> {
+    /// When a slice calls this method with null args, use the value stored under `key`
+    /// in the slice context instead, falling back to the static default if it's unset.
+    pub fn args_from_context(mut self, key: impl Into<String>) -> Self {
+        self.context_default = Some(key.into());
+        self
+    }
+
+    /// Builds the `bind_pure_cached` key from only these fields of the args object, so
+    /// calls that differ in other fields (request ids, say) share a cached result.
+    pub fn cache_key_fields(mut self, fields: &[&str]) -> Self {
+        self.cache_key_fields = Some(fields.iter().map(|field| field.to_string()).collect());
+        self
+    }
+
     pub fn bind<F>(mut self, f: F) -> LayerBuilder
     where
         F: Fn(&A, &Context) -> Result<Value> + Send + Sync + 'static,
@@ -96,6 +148,8 @@ impl<A: FromValue + ToValue + 'static> MethodBuilderBindStep<A> {
         self.layer_builder.methods.push(MethodBuilder {
             name: self.method_name,
             default_args: self.default_args,
+            context_default: self.context_default,
+            description: self.description,
             func: Some(func),
         });
 
@@ -114,51 +168,280 @@ impl<A: FromValue + ToValue + 'static> MethodBuilderBindStep<A> {
         self.layer_builder.methods.push(MethodBuilder {
             name: self.method_name,
             default_args: self.default_args,
+            context_default: self.context_default,
+            description: self.description,
             func: Some(func),
         });
 
         self.layer_builder
     }
+
+    /// Like `bind`, but the method returns a concrete type. Its `Value` form is the
+    /// method's result as usual, and the value itself can be read back with
+    /// `SliceResults::typed_result`.
+    pub fn bind_typed<R, F>(self, f: F) -> LayerBuilder
+    where
+        R: ToValue + Send + Sync + 'static,
+        F: Fn(&A, &Context) -> Result<TypedResult<R>> + Send + Sync + 'static,
+    {
+        let key = method_key(&self.layer_builder.name, &self.method_name);
+
+        self.bind(move |args: &A, context: &Context| {
+            let TypedResult(output) = f(args, context)?;
+            let value = output.to_value();
+            context.store_typed_output(key.clone(), Box::new(output));
+            Ok(value)
+        })
+    }
+
+    /// Like `bind`, but the method returns named outputs, and each one is written to the
+    /// slice context under `{layer}.{method}.{name}` for later layers to read. The
+    /// method's result is the outputs as an object.
+    pub fn bind_ctx_out<F>(self, f: F) -> LayerBuilder
+    where
+        F: Fn(&A, &Context) -> Result<HashMap<String, Value>> + Send + Sync + 'static,
+    {
+        let prefix = format!("{}.{}", self.layer_builder.name, self.method_name);
+
+        self.bind(move |args: &A, context: &Context| {
+            let outputs = f(args, context)?;
+            context.set_many(
+                outputs
+                    .iter()
+                    .map(|(name, value)| (format!("{}.{}", prefix, name), value.clone())),
+            );
+            Ok(Value::Object(outputs))
+        })
+    }
+
+    /// Like `bind_pure`, but remembers successful results by args, so repeated calls with
+    /// equal args (across slices and runs of this layer) reuse the first result. Errors
+    /// aren't cached. Concurrent calls with the same args wait for the first one to finish.
+    /// Only the `cache_key_fields` are compared, if any were set. Keys containing NaN never
+    /// equal anything, so those calls always run.
+    pub fn bind_pure_cached<F>(mut self, f: F) -> LayerBuilder
+    where
+        F: Fn(&A) -> Result<Value> + Send + Sync + 'static,
+    {
+        type Entry = Arc<Mutex<Option<Value>>>;
+        let cache: Mutex<HashMap<Value, Entry>> = Mutex::new(HashMap::new());
+        let key_fields = self.cache_key_fields.take();
+
+        self.bind_pure(move |args: &A| {
+            let key = match (&key_fields, args.to_value()) {
+                (Some(fields), Value::Object(map)) => Value::Object(
+                    map.into_iter()
+                        .filter(|(name, _)| fields.contains(name))
+                        .collect(),
+                ),
+                (_, value) => value,
+            };
+            if contains_nan(&key) {
+                return f(args);
+            }
+
+            let entry = cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(key)
+                .or_default()
+                .clone();
+
+            // A panic in `f` poisons the entry but leaves it empty, so the next call
+            // with these args recovers the lock and computes the value again
+            let mut cached = entry.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(value) = cached.as_ref() {
+                return Ok(value.clone());
+            }
+
+            let value = match f(args) {
+                Ok(value) => value,
+                Err(e) => {
+                    // Drop the empty slot, so failing args don't pile up in the cache
+                    cache
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .retain(|_, other| !Arc::ptr_eq(other, &entry));
+                    return Err(e);
+                }
+            };
+            *cached = Some(value.clone());
+            Ok(value)
+        })
+    }
+}
+
+fn contains_nan(value: &Value) -> bool {
+    match value {
+        Value::Number(Number::Float(f)) => f.is_nan(),
+        Value::Array(items) => items.iter().any(contains_nan),
+        Value::Object(map) => map.values().any(contains_nan),
+        _ => false,
+    }
 }
 
 pub struct SliceBuilder {
     name: String,
+    id: Option<String>,
     layers: std::collections::HashMap<String, std::collections::HashMap<String, Value>>,
+    result_schema: Vec<ResultField>,
+    quorum: Option<usize>,
+    priority: i32,
+    conditions: HashMap<(String, String), DependencyPredicate>,
+    seed: HashMap<String, Value>,
 }
 
 impl Slice {
     pub fn builder(name: impl Into<String>) -> SliceBuilder {
         SliceBuilder {
             name: name.into(),
+            id: None,
             layers: std::collections::HashMap::new(),
+            result_schema: Vec::new(),
+            quorum: None,
+            priority: 0,
+            conditions: HashMap::new(),
+            seed: HashMap::new(),
+        }
+    }
+
+    /// A builder pre-populated with this slice's calls, result fields, quorum, gates
+    /// and seed values, for deriving variants of a template slice.
+    pub fn to_builder(&self) -> SliceBuilder {
+        SliceBuilder {
+            name: self.name.clone(),
+            id: self.id.clone(),
+            layers: self.methods_per_layer.clone(),
+            result_schema: self.result_schema.clone(),
+            quorum: self.quorum,
+            priority: self.priority,
+            conditions: self.conditions.clone(),
+            seed: self.seed.clone(),
         }
     }
+
+    /// One slice per item, named `{prefix}_{index}`. The name given to the builder
+    /// returned by `f` is replaced.
+    pub fn map_over<T, F>(name_prefix: &str, items: impl IntoIterator<Item = T>, f: F) -> Vec<Slice>
+    where
+        F: Fn(usize, T) -> SliceBuilder,
+    {
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| f(i, item).name(format!("{}_{}", name_prefix, i)).build())
+            .collect()
+    }
 }
 
 impl SliceBuilder {
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Slices with a higher priority are dispatched first; equal priorities keep their
+    /// order. Work stealing means this only orders starts, which matters once the pool
+    /// is saturated. Defaults to 0.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Correlation id reported in `SliceStart`, `MethodStart` and `MethodComplete`
+    /// events, e.g. a trace id from an external system. Defaults to the slice name.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the args of a single call, keeping the layer's other calls. Unlike `layer`,
+    /// which replaces everything the slice calls in that layer.
+    pub fn call<A: ToValue>(
+        mut self,
+        layer_name: impl Into<String>,
+        method_name: impl Into<String>,
+        args: A,
+    ) -> Self {
+        self.layers
+            .entry(layer_name.into())
+            .or_default()
+            .insert(method_name.into(), args.to_value());
+        self
+    }
+
     pub fn layer<F>(mut self, layer_name: impl Into<String>, f: F) -> Self
     where
         F: FnOnce(LayerMethodsBuilder) -> LayerMethodsBuilder,
     {
         let builder = LayerMethodsBuilder {
             methods: std::collections::HashMap::new(),
+            conditions: HashMap::new(),
         };
 
         let builder = f(builder);
-        self.layers.insert(layer_name.into(), builder.methods);
+        let layer_name = layer_name.into();
+
+        self.conditions.retain(|(layer, _), _| *layer != layer_name);
+        for (method, predicate) in builder.conditions {
+            self.conditions
+                .insert((layer_name.clone(), method), predicate);
+        }
+
+        self.layers.insert(layer_name, builder.methods);
+        self
+    }
+
+    /// Collects the output of `layer.method` under `field` in `SliceResults::combined`.
+    pub fn result_field(
+        mut self,
+        field: impl Into<String>,
+        layer: impl Into<String>,
+        method: impl Into<String>,
+    ) -> Self {
+        self.result_schema.push(ResultField {
+            field: field.into(),
+            layer: layer.into(),
+            method: method.into(),
+        });
+        self
+    }
+
+    /// Stops starting a wave's methods once `quorum` of them succeed. Methods that
+    /// haven't started by then are never run, and results that land after the quorum
+    /// are discarded; both end up in `SliceResults::skipped` instead of
+    /// `method_results`. Running methods aren't interrupted, but `Context::is_wave_cancelled`
+    /// turns true so they can return early; in parallel mode the wave waits for those
+    /// that don't. A wave that never reaches the quorum keeps every result as usual.
+    pub fn quorum(mut self, quorum: usize) -> Self {
+        self.quorum = Some(quorum.max(1));
+        self
+    }
+
+    /// Sets `key` in this slice's context before any method runs, over the shared
+    /// context and the once layer's output. Conditional dependencies see seed values.
+    pub fn seed<V: ToValue>(mut self, key: impl Into<String>, value: V) -> Self {
+        self.seed.insert(key.into(), value.to_value());
         self
     }
 
     pub fn build(self) -> Slice {
         Slice {
             name: self.name,
+            id: self.id,
             methods_per_layer: self.layers,
+            result_schema: self.result_schema,
+            quorum: self.quorum,
+            priority: self.priority,
+            conditions: self.conditions,
+            seed: self.seed,
         }
     }
 }
 
 pub struct LayerMethodsBuilder {
     methods: std::collections::HashMap<String, Value>,
+    conditions: HashMap<String, DependencyPredicate>,
 }
 
 impl LayerMethodsBuilder {
@@ -171,14 +454,37 @@ impl LayerMethodsBuilder {
         self.methods.insert(method_name.into(), Value::Null);
         self
     }
+
+    /// Like `call`, but the method only runs if `predicate` holds for the slice context
+    /// when its wave starts. Otherwise it's marked skipped in `SliceResults`.
+    pub fn call_if<A, F>(mut self, method_name: impl Into<String>, args: A, predicate: F) -> Self
+    where
+        A: ToValue,
+        F: Fn(&Context) -> bool + Send + Sync + 'static,
+    {
+        let method_name = method_name.into();
+        self.conditions
+            .insert(method_name.clone(), Arc::new(predicate));
+        self.call(method_name, args)
+    }
 }
 
 pub struct EngineBuilder {
     layers: Vec<Layer>,
     slices: Vec<Slice>,
     dependencies: std::collections::HashMap<String, Vec<String>>,
+    conditional_dependencies: Vec<(String, String, DependencyPredicate)>,
+    env_args: Vec<(String, String)>,
+    before_method: Vec<BeforeMethodHook>,
+    after_method: Vec<AfterMethodHook>,
+    run_end: Vec<RunEndHook>,
+    shared_context: Option<Context>,
+    exclusive_groups: Vec<Vec<String>>,
     init_layer: Option<String>,
+    once_layer: Option<String>,
     observer: Observer,
+    emit_sink: Option<EmitSink>,
+    strict: bool,
     config: EngineConfig,
 }
 
@@ -188,8 +494,18 @@ impl Engine {
             layers: Vec::new(),
             slices: Vec::new(),
             dependencies: std::collections::HashMap::new(),
+            conditional_dependencies: Vec::new(),
+            env_args: Vec::new(),
+            before_method: Vec::new(),
+            after_method: Vec::new(),
+            run_end: Vec::new(),
+            shared_context: None,
+            exclusive_groups: Vec::new(),
             init_layer: None,
+            once_layer: None,
             observer: Observer::new(),
+            emit_sink: None,
+            strict: false,
             config: EngineConfig::new(),
         }
     }
@@ -206,6 +522,14 @@ impl EngineBuilder {
         self
     }
 
+    /// Unlike the init layer, which runs in every slice, the once layer runs a single
+    /// time before any slice, and the context it writes seeds every slice's context.
+    /// Its methods run with their default args, in name order.
+    pub fn once_layer(mut self, layer_name: impl Into<String>) -> Self {
+        self.once_layer = Some(layer_name.into());
+        self
+    }
+
     pub fn add_slice(mut self, slice: Slice) -> Self {
         self.slices.push(slice);
         self
@@ -224,6 +548,82 @@ impl EngineBuilder {
         self
     }
 
+    /// Adds an edge that only applies to slices whose context satisfies `predicate`.
+    ///
+    /// The predicate is checked once per slice, before any of its methods run, against
+    /// the context the slice starts with: the shared context, the once layer's output
+    /// and `SliceBuilder::seed` values. Writes made by methods (even the init layer's)
+    /// don't change the outcome, so `Engine::plan` shows the waves a run executes.
+    pub fn conditional_dependency<F>(
+        mut self,
+        layer: impl Into<String>,
+        depends_on: impl Into<String>,
+        predicate: F,
+    ) -> Self
+    where
+        F: Fn(&Context) -> bool + Send + Sync + 'static,
+    {
+        self.conditional_dependencies
+            .push((layer.into(), depends_on.into(), Arc::new(predicate)));
+        self
+    }
+
+    /// Maps arg keys to environment variables, read once per run and injected into every
+    /// method's object args (null args start from an empty object). They override the
+    /// layer's defaults, but args the slice passes explicitly win. Unset variables are skipped.
+    pub fn env_args(mut self, mapping: &[(&str, &str)]) -> Self {
+        self.env_args.extend(
+            mapping
+                .iter()
+                .map(|(key, var)| (key.to_string(), var.to_string())),
+        );
+        self
+    }
+
+    /// Rejects, at build time, any slice that references more than one of `layers`.
+    pub fn mutually_exclusive(mut self, layers: &[&str]) -> Self {
+        self.exclusive_groups
+            .push(layers.iter().map(|layer| layer.to_string()).collect());
+        self
+    }
+
+    /// Runs inline on the worker before every method, after its args are resolved
+    /// (defaults, context and env args) and before the first attempt.
+    pub fn before_method<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, &str, &str, &Context) + Send + Sync + 'static,
+    {
+        self.before_method.push(Arc::new(f));
+        self
+    }
+
+    /// Runs inline on the worker with the method's final result, once all retries are done.
+    pub fn after_method<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, &str, &str, &Result<Value>, &Context) + Send + Sync + 'static,
+    {
+        self.after_method.push(Arc::new(f));
+        self
+    }
+
+    /// Key/values copied into each slice's context before its waves run, e.g. API keys
+    /// or run parameters. Every slice gets its own copy, so writes never cross slices.
+    pub fn with_shared_context(mut self, ctx: Context) -> Self {
+        self.shared_context = Some(ctx);
+        self
+    }
+
+    /// Runs once at the end of every `run`, `run_filtered` and `run_init_only`, whether
+    /// the slices succeeded, failed or were cancelled by fail-fast. If the run panics,
+    /// the hook still fires (best effort) with empty results while the panic unwinds.
+    pub fn on_run_end<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&RunResults) + Send + Sync + 'static,
+    {
+        self.run_end.push(Arc::new(f));
+        self
+    }
+
     pub fn observer(mut self, observer: Observer) -> Self {
         self.observer = observer;
         self
@@ -237,11 +637,26 @@ impl EngineBuilder {
         self
     }
 
+    pub fn emit_sink<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, Value) + Send + Sync + 'static,
+    {
+        self.emit_sink = Some(Arc::new(f));
+        self
+    }
+
     pub fn config(mut self, config: EngineConfig) -> Self {
         self.config = config;
         self
     }
 
+    /// Makes `build` fail with `Error::MethodNotBound` if any layer declares a method it
+    /// never binds, instead of failing when a slice first calls it.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
     pub fn num_threads(mut self, threads: usize) -> Self {
         self.config = self.config.num_threads(threads);
         self
@@ -262,14 +677,26 @@ impl EngineBuilder {
         engine.config = self.config;
 
         for layer in self.layers {
+            if self.strict
+                && let Some(method) = layer.unbound_methods().first()
+            {
+                return Err(Error::MethodNotBound(
+                    method.to_string(),
+                    layer.name.clone(),
+                ));
+            }
             engine.register_layer(layer)?;
         }
 
+        if let Some(once_name) = &self.once_layer {
+            engine.set_once_layer(once_name)?;
+        }
+
         if let Some(init_name) = &self.init_layer {
             engine.set_init_layer(init_name)?;
 
             for layer_name in engine.get_layer_names() {
-                if layer_name != *init_name {
+                if layer_name != *init_name && Some(&layer_name) != self.once_layer.as_ref() {
                     engine.add_dependency(&layer_name, init_name)?;
                 }
             }
@@ -281,12 +708,46 @@ impl EngineBuilder {
             }
         }
 
+        for (layer, dep, predicate) in self.conditional_dependencies {
+            engine.add_conditional_dependency(&layer, &dep, predicate)?;
+        }
+
         for slice in self.slices {
             engine.register_slice(slice);
         }
 
+        engine.set_env_args(self.env_args);
+
+        for hook in self.before_method {
+            engine.add_before_method(hook);
+        }
+        for hook in self.after_method {
+            engine.add_after_method(hook);
+        }
+        for hook in self.run_end {
+            engine.add_run_end_hook(hook);
+        }
+
+        if let Some(ctx) = self.shared_context {
+            let entries = ctx
+                .keys()
+                .into_iter()
+                .filter_map(|key| ctx.get(&key).map(|value| (key, value)))
+                .collect();
+            engine.set_shared_context(entries);
+        }
+
+        for group in self.exclusive_groups {
+            engine.add_exclusive_group(group);
+        }
+
+        engine.validate()?;
         engine.set_observer(self.observer);
 
+        if let Some(sink) = self.emit_sink {
+            engine.set_emit_sink(sink);
+        }
+
         Ok(engine)
     }
 }
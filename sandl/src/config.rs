@@ -1,12 +1,46 @@
 use rayon::ThreadPoolBuilder;
+use std::io::Write;
+use std::time::Duration;
+
+use crate::tracker::ProgressWriter;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: usize,
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: usize, backoff: Duration) -> Self {
+        Self {
+            max_attempts,
+            backoff,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct EngineConfig {
     pub num_threads: Option<usize>,
     pub stack_size: Option<usize>,
+    pub thread_name_prefix: Option<String>,
     pub chunk_size: usize,
 
     pub batch_size: Option<usize>,
+
+    pub verbose_summary: bool,
+
+    pub run_id: Option<String>,
+    pub retry: Option<RetryPolicy>,
+    pub max_waves_per_slice: Option<usize>,
+    pub sequential: bool,
+    pub catch_panics: bool,
+    pub track_lock_contention: bool,
+    pub deep_merge_args: bool,
+    pub max_concurrent_slices: Option<usize>,
+    pub stream_capacity: usize,
+    pub progress_writer: Option<ProgressWriter>,
+    pub progress_interval: Duration,
 }
 
 impl Default for EngineConfig {
@@ -14,8 +48,21 @@ impl Default for EngineConfig {
         Self {
             num_threads: None,
             stack_size: None,
+            thread_name_prefix: None,
             batch_size: None, // No batching = process all at once
             chunk_size: 1,    // No chunking = one item per coordination
+            verbose_summary: false,
+            run_id: None,
+            retry: None,
+            max_waves_per_slice: None,
+            sequential: false,
+            catch_panics: false,
+            track_lock_contention: false,
+            deep_merge_args: false,
+            max_concurrent_slices: None,
+            stream_capacity: 1024, // Results buffered by run_streaming before workers block
+            progress_writer: None, // stdout
+            progress_interval: Duration::from_millis(50),
         }
     }
 }
@@ -35,6 +82,12 @@ impl EngineConfig {
         self
     }
 
+    /// Pool threads are named `{prefix}-{index}`, which shows up in profilers and `top`.
+    pub fn thread_name_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.thread_name_prefix = Some(prefix.into());
+        self
+    }
+
     pub fn batch_size(mut self, size: usize) -> Self {
         self.batch_size = Some(size);
         self
@@ -45,6 +98,80 @@ impl EngineConfig {
         self
     }
 
+    pub fn verbose_summary(mut self, verbose: bool) -> Self {
+        self.verbose_summary = verbose;
+        self
+    }
+
+    pub fn run_id(mut self, run_id: impl Into<String>) -> Self {
+        self.run_id = Some(run_id.into());
+        self
+    }
+
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    pub fn max_waves_per_slice(mut self, max_waves: usize) -> Self {
+        self.max_waves_per_slice = Some(max_waves);
+        self
+    }
+
+    pub fn sequential(mut self) -> Self {
+        self.sequential = true;
+        self
+    }
+
+    pub fn catch_panics(mut self, catch: bool) -> Self {
+        self.catch_panics = catch;
+        self
+    }
+
+    pub fn track_lock_contention(mut self, track: bool) -> Self {
+        self.track_lock_contention = track;
+        self
+    }
+
+    pub fn deep_merge_args(mut self, deep: bool) -> Self {
+        self.deep_merge_args = deep;
+        self
+    }
+
+    pub fn max_concurrent_slices(mut self, max: usize) -> Self {
+        self.max_concurrent_slices = Some(max.max(1));
+        self
+    }
+
+    /// Alias of `max_concurrent_slices`.
+    pub fn max_in_flight(self, max: usize) -> Self {
+        self.max_concurrent_slices(max)
+    }
+
+    pub fn stream_capacity(mut self, capacity: usize) -> Self {
+        self.stream_capacity = capacity;
+        self
+    }
+
+    /// Where tracked runs write their progress lines and summary, instead of stdout.
+    pub fn progress_writer(mut self, writer: Box<dyn Write + Send>) -> Self {
+        self.progress_writer = Some(ProgressWriter::new(writer));
+        self
+    }
+
+    pub fn progress_interval(mut self, interval: Duration) -> Self {
+        self.progress_interval = interval;
+        self
+    }
+
+    pub(crate) fn pool_settings(&self) -> PoolSettings {
+        PoolSettings {
+            num_threads: self.num_threads,
+            stack_size: self.stack_size,
+            thread_name_prefix: self.thread_name_prefix.clone(),
+        }
+    }
+
     pub(crate) fn build_thread_pool(&self) -> crate::Result<rayon::ThreadPool> {
         let mut builder = ThreadPoolBuilder::new();
 
@@ -56,35 +183,61 @@ impl EngineConfig {
             builder = builder.stack_size(stack_size);
         }
 
+        if let Some(prefix) = self.thread_name_prefix.clone() {
+            builder = builder.thread_name(move |i| format!("{}-{}", prefix, i));
+        }
+
         builder
             .build()
             .map_err(|e| crate::Error::ConfigError(format!("Failed to build thread pool: {}", e)))
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PoolSettings {
+    num_threads: Option<usize>,
+    stack_size: Option<usize>,
+    thread_name_prefix: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct RunFlags {
     pub silent: bool,
     pub with_observer: bool,
+    pub fail_fast: bool,
 }
 
 impl RunFlags {
     pub const SILENT: Self = Self {
         silent: true,
         with_observer: true,
+        fail_fast: false,
     };
     pub const SILENT_NO_OBSERVER: Self = Self {
         silent: true,
         with_observer: false,
+        fail_fast: false,
     };
     pub const TRACKED: Self = Self {
         silent: false,
         with_observer: true,
+        fail_fast: false,
+    };
+
+    pub const FAIL_FAST: Self = Self {
+        silent: false,
+        with_observer: true,
+        fail_fast: true,
     };
 
     pub fn new() -> Self {
         Self::TRACKED
     }
+
+    pub fn fail_fast(mut self) -> Self {
+        self.fail_fast = true;
+        self
+    }
 }
 
 impl Default for RunFlags {
@@ -1,49 +1,412 @@
-use crate::Value;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use crate::sharded::{Shard, ShardedMap};
+use crate::{MethodKey, Value};
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant};
 
-#[derive(Clone, Debug)]
+pub type EmitSink = Arc<dyn Fn(&str, Value) + Send + Sync>;
+pub type ChangeListener = Arc<dyn Fn(&str, &Value) + Send + Sync>;
+
+pub(crate) type TypedOutputs = HashMap<MethodKey, Box<dyn Any + Send + Sync>>;
+
+thread_local! {
+    // Contexts whose `update` or `get_or_insert_with` closure is running on this thread.
+    // That closure holds a shard lock, so touching the same context from inside it would
+    // deadlock whenever the other key shares the shard; it's rejected for every key instead.
+    static IN_CLOSURE: RefCell<Vec<*const ShardedMap>> = const { RefCell::new(Vec::new()) };
+}
+
+struct ClosureGuard(*const ShardedMap);
+
+impl ClosureGuard {
+    fn enter(data: &Arc<ShardedMap>) -> Self {
+        let map = Arc::as_ptr(data);
+        IN_CLOSURE.with(|maps| maps.borrow_mut().push(map));
+        Self(map)
+    }
+}
+
+impl Drop for ClosureGuard {
+    fn drop(&mut self) {
+        IN_CLOSURE.with(|maps| {
+            let mut maps = maps.borrow_mut();
+            if let Some(index) = maps.iter().rposition(|map| *map == self.0) {
+                maps.remove(index);
+            }
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockStats {
+    pub acquisitions: u64,
+    pub contended: u64,
+    pub wait: Duration,
+}
+
+#[derive(Default)]
+struct LockCounters {
+    acquisitions: AtomicU64,
+    contended: AtomicU64,
+    wait_nanos: AtomicU64,
+}
+
+impl LockCounters {
+    fn record(&self, wait: Option<Duration>) {
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        if let Some(wait) = wait {
+            self.contended.fetch_add(1, Ordering::Relaxed);
+            self.wait_nanos
+                .fetch_add(wait.as_nanos() as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A copy of a context's local entries, taken by `Context::snapshot`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContextSnapshot(HashMap<String, Value>);
+
+impl ContextSnapshot {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(Clone)]
 pub struct Context {
-    data: Arc<RwLock<HashMap<String, Value>>>,
+    data: Arc<ShardedMap>,
+    sink: Option<(Arc<str>, EmitSink)>,
+    lock_counters: Option<Arc<LockCounters>>,
+    parent: Option<Arc<Context>>,
+    listeners: Arc<RwLock<Vec<ChangeListener>>>,
+    typed_outputs: Arc<Mutex<TypedOutputs>>,
+    wave_cancelled: Arc<AtomicBool>,
 }
 
 impl Context {
     pub fn new() -> Self {
         Self {
-            data: Arc::new(RwLock::new(HashMap::new())),
+            data: Arc::new(ShardedMap::new()),
+            sink: None,
+            lock_counters: None,
+            parent: None,
+            listeners: Arc::default(),
+            typed_outputs: Arc::default(),
+            wave_cancelled: Arc::default(),
+        }
+    }
+
+    pub(crate) fn with_sink(slice: &str, sink: EmitSink) -> Self {
+        Self {
+            data: Arc::new(ShardedMap::new()),
+            sink: Some((Arc::from(slice), sink)),
+            lock_counters: None,
+            parent: None,
+            listeners: Arc::default(),
+            typed_outputs: Arc::default(),
+            wave_cancelled: Arc::default(),
+        }
+    }
+
+    /// A scope layered over this context: reads fall through to the parent when a key
+    /// isn't set locally, while writes and removals only touch the child.
+    pub fn child(&self) -> Context {
+        Self {
+            data: Arc::new(ShardedMap::new()),
+            sink: self.sink.clone(),
+            lock_counters: self.lock_counters.clone(),
+            parent: Some(Arc::new(self.clone())),
+            listeners: self.listeners.clone(),
+            typed_outputs: self.typed_outputs.clone(),
+            wave_cancelled: self.wave_cancelled.clone(),
+        }
+    }
+
+    /// True once the running wave has no use for further results, e.g. because the
+    /// slice's quorum was reached. Long-running methods can poll this and return early;
+    /// whatever they return is then discarded.
+    pub fn is_wave_cancelled(&self) -> bool {
+        self.wave_cancelled.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn set_wave_cancelled(&self, cancelled: bool) {
+        self.wave_cancelled.store(cancelled, Ordering::SeqCst);
+    }
+
+    // Concrete return values of `bind_typed` methods, collected into SliceResults
+    // once the slice is done
+    pub(crate) fn store_typed_output(&self, key: MethodKey, output: Box<dyn Any + Send + Sync>) {
+        self.typed_outputs.lock().unwrap().insert(key, output);
+    }
+
+    pub(crate) fn take_typed_outputs(&self) -> TypedOutputs {
+        std::mem::take(&mut *self.typed_outputs.lock().unwrap())
+    }
+
+    /// Flushes the child's local writes into its parent, overwriting existing keys.
+    /// Does nothing on a context without a parent.
+    pub fn merge_into_parent(self) {
+        let Some(parent) = &self.parent else {
+            return;
+        };
+
+        for mut shard in self.write_all() {
+            for (key, value) in std::mem::take(&mut *shard) {
+                parent.set(key, value);
+            }
+        }
+    }
+
+    pub(crate) fn with_lock_stats(mut self) -> Self {
+        self.lock_counters = Some(Arc::new(LockCounters::default()));
+        self
+    }
+
+    /// Acquisition counts and time spent waiting on the context locks, if tracking is enabled.
+    pub fn lock_stats(&self) -> Option<LockStats> {
+        self.lock_counters.as_ref().map(|counters| LockStats {
+            acquisitions: counters.acquisitions.load(Ordering::Relaxed),
+            contended: counters.contended.load(Ordering::Relaxed),
+            wait: Duration::from_nanos(counters.wait_nanos.load(Ordering::Relaxed)),
+        })
+    }
+
+    fn check_not_in_closure(&self) {
+        let map = Arc::as_ptr(&self.data);
+        if IN_CLOSURE.with(|maps| maps.borrow().contains(&map)) {
+            panic!("Context accessed from inside its own update or get_or_insert_with closure");
+        }
+    }
+
+    // A panicking `update` closure poisons its shard without changing any value, so the
+    // poison is ignored rather than making the shard's keys unusable
+    fn read_lock<'a>(&self, shard: &'a Shard) -> RwLockReadGuard<'a, HashMap<String, Value>> {
+        self.check_not_in_closure();
+        let Some(counters) = &self.lock_counters else {
+            return shard.read().unwrap_or_else(PoisonError::into_inner);
+        };
+
+        if let Ok(guard) = shard.try_read() {
+            counters.record(None);
+            return guard;
+        }
+
+        let start = Instant::now();
+        let guard = shard.read().unwrap_or_else(PoisonError::into_inner);
+        counters.record(Some(start.elapsed()));
+        guard
+    }
+
+    fn write_lock<'a>(&self, shard: &'a Shard) -> RwLockWriteGuard<'a, HashMap<String, Value>> {
+        self.check_not_in_closure();
+        let Some(counters) = &self.lock_counters else {
+            return shard.write().unwrap_or_else(PoisonError::into_inner);
+        };
+
+        if let Ok(guard) = shard.try_write() {
+            counters.record(None);
+            return guard;
+        }
+
+        let start = Instant::now();
+        let guard = shard.write().unwrap_or_else(PoisonError::into_inner);
+        counters.record(Some(start.elapsed()));
+        guard
+    }
+
+    fn read(&self, key: &str) -> RwLockReadGuard<'_, HashMap<String, Value>> {
+        self.read_lock(self.data.shard(key))
+    }
+
+    fn write(&self, key: &str) -> RwLockWriteGuard<'_, HashMap<String, Value>> {
+        self.write_lock(self.data.shard(key))
+    }
+
+    // Every shard, locked in index order
+    fn read_all(&self) -> Vec<RwLockReadGuard<'_, HashMap<String, Value>>> {
+        self.data
+            .shards()
+            .iter()
+            .map(|shard| self.read_lock(shard))
+            .collect()
+    }
+
+    fn write_all(&self) -> Vec<RwLockWriteGuard<'_, HashMap<String, Value>>> {
+        self.data
+            .shards()
+            .iter()
+            .map(|shard| self.write_lock(shard))
+            .collect()
+    }
+
+    /// Forwards a single element to the engine's emit sink without buffering it.
+    /// Methods that stream their output this way should return `Value::Null`.
+    /// Without a configured sink, emitted values are dropped.
+    pub fn emit(&self, value: Value) {
+        if let Some((slice, sink)) = &self.sink {
+            sink(slice, value);
         }
     }
 
+    pub fn has_sink(&self) -> bool {
+        self.sink.is_some()
+    }
+
     pub fn get(&self, key: &str) -> Option<Value> {
-        self.data.read().unwrap().get(key).cloned()
+        if let Some(value) = self.read(key).get(key).cloned() {
+            return Some(value);
+        }
+
+        self.parent.as_ref().and_then(|parent| parent.get(key))
     }
 
     pub fn set(&self, key: impl Into<String>, value: Value) {
-        self.data.write().unwrap().insert(key.into(), value);
+        let key = key.into();
+        if self.listeners.read().unwrap().is_empty() {
+            self.write(&key).insert(key, value);
+            return;
+        }
+
+        self.write(&key).insert(key.clone(), value.clone());
+        self.notify(&key, &value);
+    }
+
+    /// Calls `f` with the key and new value on every `set`, `set_from`, `set_many` and `update`,
+    /// from whichever thread made the write. Children share their parent's listeners.
+    pub fn on_change(&self, f: ChangeListener) {
+        self.listeners.write().unwrap().push(f);
+    }
+
+    fn notify(&self, key: &str, value: &Value) {
+        for listener in self.listeners.read().unwrap().iter() {
+            listener(key, value);
+        }
+    }
+
+    /// Read-modify-write under a single write lock, so concurrent methods updating
+    /// the same key can't lose each other's changes. Returns the stored value.
+    ///
+    /// `f` runs while the lock is held: it must not use this context (doing so panics),
+    /// and other threads touching keys in the same shard, or walking every key with
+    /// `keys`, `len` and the like, wait until it returns. Keep it short.
+    pub fn update<F>(&self, key: impl Into<String>, f: F) -> Value
+    where
+        F: FnOnce(Option<Value>) -> Value,
+    {
+        let key = key.into();
+        let mut data = self.write(&key);
+
+        // Cloned rather than removed, so a panic in `f` leaves the key as it was
+        let current = data
+            .get(&key)
+            .cloned()
+            .or_else(|| self.parent.as_ref().and_then(|parent| parent.get(&key)));
+        let updated = {
+            let _guard = ClosureGuard::enter(&self.data);
+            f(current)
+        };
+
+        data.insert(key.clone(), updated.clone());
+        drop(data);
+        self.notify(&key, &updated);
+        updated
+    }
+
+    /// Like `update`, `f` runs under the key's write lock and must not use this context.
+    pub fn get_or_insert_with<F>(&self, key: impl Into<String>, f: F) -> Value
+    where
+        F: FnOnce() -> Value,
+    {
+        let key = key.into();
+        let mut data = self.write(&key);
+
+        if let Some(value) = data.get(&key) {
+            return value.clone();
+        }
+        if let Some(value) = self.parent.as_ref().and_then(|parent| parent.get(&key)) {
+            return value;
+        }
+
+        let value = {
+            let _guard = ClosureGuard::enter(&self.data);
+            f()
+        };
+        data.insert(key, value.clone());
+        value
     }
 
     pub fn contains(&self, key: &str) -> bool {
-        self.data.read().unwrap().contains_key(key)
+        self.read(key).contains_key(key) || self.parent.as_ref().is_some_and(|p| p.contains(key))
     }
 
     pub fn remove(&self, key: &str) -> Option<Value> {
-        self.data.write().unwrap().remove(key)
+        self.write(key).remove(key)
     }
 
     pub fn keys(&self) -> Vec<String> {
-        self.data.read().unwrap().keys().cloned().collect()
+        let mut keys: Vec<String> = self
+            .read_all()
+            .iter()
+            .flat_map(|shard| shard.keys().cloned().collect::<Vec<_>>())
+            .collect();
+
+        if let Some(parent) = &self.parent {
+            let local: HashSet<String> = keys.iter().cloned().collect();
+            keys.extend(parent.keys().into_iter().filter(|key| !local.contains(key)));
+        }
+
+        keys
     }
 
     pub fn clear(&self) {
-        self.data.write().unwrap().clear();
+        for mut shard in self.write_all() {
+            shard.clear();
+        }
+    }
+
+    /// Copies the local entries; a child's snapshot doesn't include its parent's.
+    pub fn snapshot(&self) -> ContextSnapshot {
+        ContextSnapshot(
+            self.read_all()
+                .iter()
+                .flat_map(|shard| shard.iter().map(|(k, v)| (k.clone(), v.clone())))
+                .collect(),
+        )
+    }
+
+    /// Replaces the local entries with the snapshot's, e.g. to roll back after a failed
+    /// attempt. Change listeners aren't called.
+    pub fn restore(&self, snapshot: ContextSnapshot) {
+        let mut shards = self.write_all();
+        for shard in shards.iter_mut() {
+            shard.clear();
+        }
+        for (key, value) in snapshot.0 {
+            shards[self.data.index(&key)].insert(key, value);
+        }
     }
 
     pub fn len(&self) -> usize {
-        self.data.read().unwrap().len()
+        match &self.parent {
+            Some(_) => self.keys().len(),
+            None => self.read_all().iter().map(|shard| shard.len()).sum(),
+        }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.data.read().unwrap().is_empty()
+        self.read_all().iter().all(|shard| shard.is_empty())
+            && self.parent.as_ref().is_none_or(|p| p.is_empty())
     }
 
     pub fn get_as<T>(&self, key: &str) -> crate::Result<T>
@@ -56,12 +419,83 @@ impl Context {
         T::from_value(&value)
     }
 
+    /// Falls back to `default` when the key is missing or holds a different type.
+    pub fn get_as_or<T>(&self, key: &str, default: T) -> T
+    where
+        T: crate::FromValue,
+    {
+        self.get_as_opt(key).ok().flatten().unwrap_or(default)
+    }
+
+    /// `Ok(None)` only when the key is absent; a value of the wrong type is still an error.
+    pub fn get_as_opt<T>(&self, key: &str) -> crate::Result<Option<T>>
+    where
+        T: crate::FromValue,
+    {
+        self.get(key).map(|value| T::from_value(&value)).transpose()
+    }
+
     pub fn set_from<T>(&self, key: impl Into<String>, value: T)
     where
         T: crate::ToValue,
     {
         self.set(key, value.to_value());
     }
+
+    /// Inserts every entry while holding the write locks once.
+    pub fn set_many(&self, entries: impl IntoIterator<Item = (String, Value)>) {
+        let notify = !self.listeners.read().unwrap().is_empty();
+        let mut written = Vec::new();
+
+        {
+            let mut shards = self.write_all();
+            for (key, value) in entries {
+                if notify {
+                    written.push((key.clone(), value.clone()));
+                }
+                shards[self.data.index(&key)].insert(key, value);
+            }
+        }
+
+        for (key, value) in &written {
+            self.notify(key, value);
+        }
+    }
+
+    /// Reads and converts every key while holding the read locks once, failing on the first
+    /// missing or mistyped one.
+    pub fn get_many<T>(&self, keys: &[&str]) -> crate::Result<Vec<T>>
+    where
+        T: crate::FromValue,
+    {
+        let shards = self.read_all();
+
+        keys.iter()
+            .map(|key| {
+                let value = match shards[self.data.index(key)].get(*key) {
+                    Some(value) => value.clone(),
+                    None => self
+                        .parent
+                        .as_ref()
+                        .and_then(|parent| parent.get(key))
+                        .ok_or_else(|| {
+                            crate::Error::ConfigError(format!("Key '{}' not found in context", key))
+                        })?,
+                };
+                T::from_value(&value)
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Context")
+            .field("data", &self.snapshot().0)
+            .field("has_sink", &self.sink.is_some())
+            .field("parent", &self.parent)
+            .finish()
+    }
 }
 
 impl Default for Context {
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::*;
+
+const NAME_COLUMN: &str = "name";
+
+impl Engine {
+    /// Builds one slice per row of a delimited file, calling `layer.method` with the row as args.
+    /// The `name` column becomes the slice name; every other column is passed as a string.
+    pub fn slices_from_delimited(
+        path: impl AsRef<Path>,
+        layer: &str,
+        method: &str,
+        delimiter: u8,
+    ) -> crate::Result<Vec<Slice>> {
+        read_delimited(path.as_ref(), layer, method, delimiter, false)
+    }
+
+    /// Like `slices_from_delimited`, but cells that parse as integers or floats become numbers.
+    pub fn slices_from_delimited_inferred(
+        path: impl AsRef<Path>,
+        layer: &str,
+        method: &str,
+        delimiter: u8,
+    ) -> crate::Result<Vec<Slice>> {
+        read_delimited(path.as_ref(), layer, method, delimiter, true)
+    }
+}
+
+fn read_delimited(
+    path: &Path,
+    layer: &str,
+    method: &str,
+    delimiter: u8,
+    infer_numbers: bool,
+) -> crate::Result<Vec<Slice>> {
+    let read_error =
+        |e: csv::Error| Error::ConfigError(format!("Failed to read {}: {}", path.display(), e));
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_path(path)
+        .map_err(read_error)?;
+
+    let headers = reader.headers().map_err(read_error)?.clone();
+    let name_index = headers
+        .iter()
+        .position(|h| h == NAME_COLUMN)
+        .ok_or_else(|| {
+            Error::ConfigError(format!(
+                "{} has no '{}' column",
+                path.display(),
+                NAME_COLUMN
+            ))
+        })?;
+
+    let mut slices = Vec::new();
+
+    for record in reader.records() {
+        let record = record.map_err(read_error)?;
+
+        let mut args = HashMap::new();
+        for (index, (header, cell)) in headers.iter().zip(record.iter()).enumerate() {
+            if index != name_index {
+                args.insert(header.to_string(), parse_cell(cell, infer_numbers));
+            }
+        }
+
+        let mut methods_args = HashMap::new();
+        methods_args.insert(method.to_string(), Value::Object(args));
+
+        let name = record.get(name_index).unwrap_or_default().to_string();
+        slices.push(Slice::new(name).with_layer(LayerArgs {
+            layer: layer.to_string(),
+            methods_args,
+        }));
+    }
+
+    Ok(slices)
+}
+
+fn parse_cell(cell: &str, infer_numbers: bool) -> Value {
+    if infer_numbers {
+        if let Ok(i) = cell.parse::<i64>() {
+            return Value::from(i);
+        }
+        if let Ok(f) = cell.parse::<f64>()
+            && f.is_finite()
+        {
+            return Value::from(f);
+        }
+    }
+
+    Value::from(cell)
+}
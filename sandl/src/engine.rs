@@ -1,16 +1,118 @@
-use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
-use std::time::Instant;
-
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::semaphore::Semaphore;
 use crate::tracker::ProgressTracker;
 use crate::*;
 
+struct RunState {
+    run_id: String,
+    execution_order: Vec<String>,
+    use_observer: bool,
+    fail_fast: bool,
+    halted: AtomicBool,
+    cancel: Option<CancellationToken>,
+    env_args: Vec<(String, Value)>,
+}
+
+impl RunState {
+    fn new(run_id: String, execution_order: Vec<String>, flags: RunFlags) -> Self {
+        Self {
+            run_id,
+            execution_order,
+            use_observer: flags.with_observer,
+            fail_fast: flags.fail_fast,
+            halted: AtomicBool::new(false),
+            cancel: None,
+            env_args: Vec::new(),
+        }
+    }
+
+    fn halt(&self) {
+        self.halted.store(true, Ordering::SeqCst);
+    }
+
+    fn is_halted(&self) -> bool {
+        self.halted.load(Ordering::SeqCst) || self.is_cancelled()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+    }
+}
+
+/// Stops a run from the outside, e.g. from a Ctrl-C handler. Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+pub type DependencyPredicate = Arc<dyn Fn(&Context) -> bool + Send + Sync>;
+pub type BeforeMethodHook = Arc<dyn Fn(&str, &str, &str, &Context) + Send + Sync>;
+pub type AfterMethodHook = Arc<dyn Fn(&str, &str, &str, &Result<Value>, &Context) + Send + Sync>;
+pub type RunEndHook = Arc<dyn Fn(&RunResults) + Send + Sync>;
+
+pub type MethodWaves = Vec<Vec<MethodKey>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionPlan {
+    pub execution_order: Vec<String>,
+    pub slices: Vec<(String, MethodWaves)>,
+}
+
+impl ExecutionPlan {
+    pub fn waves(&self, slice: &str) -> Option<&MethodWaves> {
+        self.slices
+            .iter()
+            .find(|(name, _)| name == slice)
+            .map(|(_, waves)| waves)
+    }
+}
+
+// The pool is kept across runs and only rebuilt when the settings it was built with change
+struct CachedPool {
+    settings: PoolSettings,
+    pool: Arc<rayon::ThreadPool>,
+}
+
 pub struct Engine {
     slices: Vec<Slice>,
     layers: HashMap<String, Layer>,
+    // Layer and method names, shared by the result keys of every slice
+    names: HashMap<String, Arc<str>>,
+    layer_permits: HashMap<String, Arc<Semaphore>>,
     dependencies: HashMap<String, Vec<String>>,
+    conditional_dependencies: HashMap<String, Vec<(String, DependencyPredicate)>>,
+    env_args: Vec<(String, String)>,
+    before_method: Vec<BeforeMethodHook>,
+    after_method: Vec<AfterMethodHook>,
+    run_end: Vec<RunEndHook>,
+    shared_context: HashMap<String, Value>,
+    once_layer: Option<String>,
+    once_seed: OnceLock<std::result::Result<HashMap<String, Value>, String>>,
+    pool: Mutex<Option<CachedPool>>,
+    exclusive_groups: Vec<Vec<String>>,
     init_layer: Option<String>,
     observer: Observer,
+    emit_sink: Option<EmitSink>,
     pub config: EngineConfig,
     pub flags: RunFlags,
 }
@@ -20,15 +122,31 @@ impl Engine {
         Self {
             slices: Vec::new(),
             layers: HashMap::new(),
+            names: HashMap::new(),
+            layer_permits: HashMap::new(),
             dependencies: HashMap::new(),
+            conditional_dependencies: HashMap::new(),
+            env_args: Vec::new(),
+            before_method: Vec::new(),
+            after_method: Vec::new(),
+            run_end: Vec::new(),
+            shared_context: HashMap::new(),
+            once_layer: None,
+            once_seed: OnceLock::new(),
+            pool: Mutex::new(None),
+            exclusive_groups: Vec::new(),
             init_layer: None,
             observer: Observer::new(),
+            emit_sink: None,
             config: EngineConfig::new(),
             flags: RunFlags::new(),
         }
     }
 
-    fn topological_sort(&self) -> crate::Result<Vec<String>> {
+    /// Orders every registered layer, including ones no slice calls, so a cycle or a
+    /// dependency on an unknown layer anywhere in the engine is an error here. Per-slice
+    /// waves only ever look at the layers a slice uses.
+    pub fn topological_sort(&self) -> crate::Result<Vec<String>> {
         let mut in_degree: HashMap<String, usize> = HashMap::new();
         let mut graph: HashMap<String, Vec<String>> = HashMap::new();
 
@@ -37,25 +155,42 @@ impl Engine {
             graph.insert(layer_name.clone(), Vec::new());
         }
 
-        for (layer, deps) in &self.dependencies {
-            *in_degree.get_mut(layer).unwrap() = deps.len();
-            for dep in deps {
-                if !self.layers.contains_key(dep) {
-                    return Err(crate::Error::LayerNotFound(dep.clone()));
+        // Conditional edges are ordered like static ones, whether or not they end up applying
+        let conditional = self
+            .conditional_dependencies
+            .iter()
+            .map(|(layer, deps)| (layer, deps.iter().map(|(dep, _)| dep).collect::<Vec<_>>()));
+        let edges = self
+            .dependencies
+            .iter()
+            .map(|(layer, deps)| (layer, deps.iter().collect::<Vec<_>>()))
+            .chain(conditional);
+
+        let mut deps_of: HashMap<&String, Vec<&String>> = HashMap::new();
+        for (layer, deps) in edges {
+            let Some(degree) = in_degree.get_mut(layer) else {
+                return Err(crate::Error::LayerNotFound(layer.clone()));
+            };
+            *degree += deps.len();
+            for dep in &deps {
+                if !self.layers.contains_key(*dep) {
+                    return Err(crate::Error::LayerNotFound((*dep).clone()));
                 }
-                graph.get_mut(dep).unwrap().push(layer.clone());
+                graph.get_mut(*dep).unwrap().push(layer.clone());
             }
+            deps_of.entry(layer).or_default().extend(deps);
         }
 
-        let mut queue: Vec<String> = in_degree
+        // Min-heap on the name so independent layers always come out in the same order
+        let mut queue: BinaryHeap<Reverse<String>> = in_degree
             .iter()
             .filter(|(_, deg)| **deg == 0)
-            .map(|(name, _)| name.clone())
+            .map(|(name, _)| Reverse(name.clone()))
             .collect();
 
         let mut result = Vec::new();
 
-        while let Some(node) = queue.pop() {
+        while let Some(Reverse(node)) = queue.pop() {
             result.push(node.clone());
 
             if let Some(neighbors) = graph.get(&node) {
@@ -63,16 +198,18 @@ impl Engine {
                     let deg = in_degree.get_mut(neighbor).unwrap();
                     *deg -= 1;
                     if *deg == 0 {
-                        queue.push(neighbor.clone());
+                        queue.push(Reverse(neighbor.clone()));
                     }
                 }
             }
         }
 
         if result.len() != self.layers.len() {
-            return Err(crate::Error::ConfigError(
-                "Circular dependency detected in layers".to_string(),
-            ));
+            let cycle = Self::find_cycle(&in_degree, &deps_of);
+            return Err(crate::Error::ConfigError(format!(
+                "Circular dependency: {}",
+                cycle.join(" -> ")
+            )));
         }
 
         if let Some(init_name) = &self.init_layer {
@@ -83,106 +220,412 @@ impl Engine {
         Ok(result)
     }
 
-    fn compute_method_waves(
-        &self,
-        slice: &Slice,
-        execution_order: &[String],
-    ) -> crate::Result<Vec<Vec<(String, String)>>> {
-        let mut waves: Vec<Vec<(String, String)>> = Vec::new();
-        let mut remaining_layers: HashSet<String> = execution_order
+    // Layers left with a nonzero in-degree after Kahn's algorithm each wait on at least
+    // one other leftover layer, so walking those deps must eventually revisit a layer.
+    // Returned in dependency order (`dep -> layer`) with the first layer repeated at the end.
+    fn find_cycle(
+        in_degree: &HashMap<String, usize>,
+        deps_of: &HashMap<&String, Vec<&String>>,
+    ) -> Vec<String> {
+        let remaining: HashSet<&String> = in_degree
             .iter()
-            .filter(|layer| slice.has_layer(layer))
-            .cloned()
+            .filter(|(_, deg)| **deg > 0)
+            .map(|(name, _)| name)
             .collect();
-        let mut completed_layers: HashSet<String> = HashSet::new();
 
-        while !remaining_layers.is_empty() {
-            let mut current_wave = Vec::new();
+        let Some(mut node) = remaining.iter().min().copied() else {
+            return Vec::new();
+        };
 
-            for layer_name in &remaining_layers.clone() {
-                let deps = self.dependencies.get(layer_name);
-                let deps_satisfied = deps
-                    .map(|d| d.iter().all(|dep| completed_layers.contains(dep)))
-                    .unwrap_or(true);
+        let mut path: Vec<&String> = Vec::new();
+        let mut seen: HashMap<&String, usize> = HashMap::new();
 
-                if deps_satisfied {
-                    if let Ok(methods) = slice.get_layer_methods(layer_name) {
-                        for method_name in methods {
-                            current_wave.push((layer_name.clone(), method_name.to_string()));
-                        }
-                    }
-                }
-            }
+        while !seen.contains_key(node) {
+            seen.insert(node, path.len());
+            path.push(node);
 
-            if current_wave.is_empty() {
-                return Err(crate::Error::ConfigError(
-                    "Unable to compute method waves".to_string(),
-                ));
-            }
+            node = deps_of
+                .get(node)
+                .and_then(|deps| deps.iter().filter(|dep| remaining.contains(**dep)).min())
+                .copied()
+                .expect("leftover layer has a leftover dependency");
+        }
+
+        let mut cycle: Vec<String> = path[seen[node]..].iter().map(|s| s.to_string()).collect();
+        cycle.push(node.clone());
+        cycle.reverse();
+        cycle
+    }
+
+    // Waves borrow their names from the slice and the execution order, so computing
+    // them allocates nothing per method
+    fn compute_next_wave<'a>(
+        &self,
+        slice: &'a Slice,
+        execution_order: &'a [String],
+        completed_layers: &mut HashSet<&'a str>,
+        waves_so_far: usize,
+        active_edges: &HashSet<(&str, &str)>,
+    ) -> crate::Result<Option<Vec<(&'a str, &'a str)>>> {
+        let is_ready = |layer_name: &str, completed_layers: &HashSet<&'a str>| {
+            let deps_satisfied = self
+                .dependencies
+                .get(layer_name)
+                .map(|d| d.iter().all(|dep| completed_layers.contains(dep.as_str())))
+                .unwrap_or(true);
+
+            let conditional_satisfied = self
+                .conditional_dependencies
+                .get(layer_name)
+                .map(|d| {
+                    d.iter().all(|(dep, _)| {
+                        completed_layers.contains(dep.as_str())
+                            || !active_edges.contains(&(layer_name, dep.as_str()))
+                    })
+                })
+                .unwrap_or(true);
+
+            deps_satisfied && conditional_satisfied
+        };
 
-            let wave_layers: HashSet<String> = current_wave
+        let calls_nothing = |layer_name: &str| {
+            slice
+                .get_layer_methods(layer_name)
+                .map_or(true, |methods| methods.is_empty())
+        };
+
+        // A layer the slice lists without calling any method is done as soon as it's
+        // ready, which may in turn unblock other empty layers
+        let remaining_layers = loop {
+            let remaining_layers: Vec<&'a str> = execution_order
+                .iter()
+                .map(|layer| layer.as_str())
+                .filter(|layer| slice.has_layer(layer) && !completed_layers.contains(layer))
+                .collect();
+
+            let satisfied: Vec<&'a str> = remaining_layers
                 .iter()
-                .map(|(layer, _)| layer.clone())
+                .copied()
+                .filter(|layer| calls_nothing(layer) && is_ready(layer, completed_layers))
                 .collect();
 
-            for layer in &wave_layers {
-                remaining_layers.remove(layer);
-                completed_layers.insert(layer.clone());
+            if satisfied.is_empty() {
+                break remaining_layers;
             }
+            completed_layers.extend(satisfied);
+        };
 
-            waves.push(current_wave);
+        if remaining_layers.is_empty() {
+            return Ok(None);
         }
 
-        Ok(waves)
+        let mut current_wave = Vec::new();
+
+        for layer_name in &remaining_layers {
+            if is_ready(layer_name, completed_layers)
+                && let Ok(mut methods) = slice.get_layer_methods(layer_name)
+            {
+                methods.sort();
+                for method_name in methods {
+                    current_wave.push((*layer_name, method_name));
+                }
+            }
+        }
+
+        if current_wave.is_empty() {
+            return Err(crate::Error::ConfigError(
+                "Unable to compute method waves".to_string(),
+            ));
+        }
+
+        for (layer, _) in &current_wave {
+            completed_layers.insert(*layer);
+        }
+
+        if let Some(max_waves) = self.config.max_waves_per_slice
+            && waves_so_far + 1 > max_waves
+        {
+            return Err(crate::Error::ConfigError(format!(
+                "Slice '{}' requires more than {} waves",
+                slice.get_name(),
+                max_waves
+            )));
+        }
+
+        Ok(Some(current_wave))
     }
 
-    fn execute_slice(
+    // Each conditional edge is decided once per slice, against the context the slice
+    // starts with, so every wave (and `plan`) sees the same graph shape
+    fn active_conditional_edges(&self, ctx: &Context) -> HashSet<(&str, &str)> {
+        self.conditional_dependencies
+            .iter()
+            .flat_map(|(layer, deps)| {
+                deps.iter()
+                    .filter(|(_, predicate)| predicate(ctx))
+                    .map(move |(dep, _)| (layer.as_str(), dep.as_str()))
+            })
+            .collect()
+    }
+
+    fn compute_method_waves(
         &self,
         slice: &Slice,
         execution_order: &[String],
-        use_observer: bool,
-    ) -> Result<SliceResults> {
+        ctx: &Context,
+    ) -> crate::Result<MethodWaves> {
+        let mut completed_layers = HashSet::new();
+        let mut waves = Vec::new();
+        let active_edges = self.active_conditional_edges(ctx);
+
+        while let Some(wave) = self.compute_next_wave(
+            slice,
+            execution_order,
+            &mut completed_layers,
+            waves.len(),
+            &active_edges,
+        )? {
+            let wave = wave
+                .into_iter()
+                .map(|(layer, method)| (self.intern(layer), self.intern(method)))
+                .collect();
+            waves.push(wave);
+        }
+
+        Ok(waves)
+    }
+
+    /// The layer order and per-slice method waves a run would use, without running anything.
+    /// Conditional dependencies are evaluated against the context a slice starts with: the
+    /// shared context, the once layer's output and the slice's seed values. Planning never
+    /// runs the once layer, so its output is only included when an earlier run on this
+    /// engine has produced it.
+    pub fn plan(&self) -> crate::Result<ExecutionPlan> {
+        let execution_order = self.topological_sort()?;
+
+        let slices = self
+            .slices
+            .iter()
+            .map(|slice| {
+                let context = self.plan_context(slice);
+                let waves = self.compute_method_waves(slice, &execution_order, &context)?;
+                Ok((slice.get_name().to_string(), waves))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        Ok(ExecutionPlan {
+            execution_order,
+            slices,
+        })
+    }
+
+    // Like new_slice_context, but without forcing the once layer to run
+    fn plan_context(&self, slice: &Slice) -> Context {
+        let context = Context::new();
+        let once_output = self.once_seed.get().and_then(|seed| seed.as_ref().ok());
+
+        for (key, value) in once_output
+            .unwrap_or(&self.shared_context)
+            .iter()
+            .chain(&slice.seed)
+        {
+            context.set(key.clone(), value.clone());
+        }
+        context
+    }
+
+    fn execute_slice(&self, slice: &Slice, state: &RunState) -> Result<SliceResults> {
         use rayon::prelude::*;
 
+        let use_observer = state.use_observer;
+
         let slice_name = slice.get_name().to_string();
         let slice_start = Instant::now();
 
         if use_observer {
             self.observer.emit(EngineEvent::SliceStart {
+                run_id: state.run_id.clone(),
                 slice: slice_name.clone(),
+                slice_id: slice.get_id().to_string(),
             });
         }
 
-        let waves = self.compute_method_waves(slice, execution_order)?;
         let mut results = SliceResults::new();
 
-        let context = Context::new();
+        let context = match self.new_slice_context(slice, state) {
+            Ok(context) => context,
+            Err(e) => {
+                if use_observer {
+                    self.observer.emit(EngineEvent::SliceFailed {
+                        run_id: state.run_id.clone(),
+                        slice: slice_name,
+                        slice_id: slice.get_id().to_string(),
+                        error: e.to_string(),
+                    });
+                }
+                return Err(e);
+            }
+        };
 
-        for wave in waves {
-            let wave_results: Vec<((String, String), Result<Value>)> = wave
-                .par_iter()
-                .map(|(layer_name, method_name)| {
-                    let result = if use_observer {
-                        self.observe_execute_method(slice, layer_name, method_name, &context)
-                    } else {
-                        self.execute_method(slice, layer_name, method_name, &context)
-                    };
+        // Waves are computed one at a time, so a wave error still leaves earlier results
+        let mut completed_layers = HashSet::new();
+        let mut wave_count = 0;
+        let active_edges = self.active_conditional_edges(&context);
+
+        loop {
+            let wave = match self.compute_next_wave(
+                slice,
+                &state.execution_order,
+                &mut completed_layers,
+                wave_count,
+                &active_edges,
+            ) {
+                Ok(Some(wave)) => wave,
+                Ok(None) => break,
+                Err(e) => {
+                    if use_observer {
+                        self.observer.emit(EngineEvent::SliceFailed {
+                            run_id: state.run_id.clone(),
+                            slice: slice_name,
+                            slice_id: slice.get_id().to_string(),
+                            error: e.to_string(),
+                        });
+                    }
+                    return Err(e);
+                }
+            };
+            if state.is_cancelled() {
+                let e = crate::Error::Cancelled;
+                if use_observer {
+                    self.observer.emit(EngineEvent::SliceFailed {
+                        run_id: state.run_id.clone(),
+                        slice: slice_name,
+                        slice_id: slice.get_id().to_string(),
+                        error: e.to_string(),
+                    });
+                }
+                return Err(e);
+            }
 
-                    ((layer_name.clone(), method_name.clone()), result)
-                })
-                .collect();
+            let wave_index = wave_count;
+            let wave_start = Instant::now();
+            wave_count += 1;
+
+            if use_observer {
+                self.observer.emit(EngineEvent::WaveStart {
+                    run_id: state.run_id.clone(),
+                    slice: slice_name.clone(),
+                    wave_index,
+                    size: wave.len(),
+                });
+            }
+
+            // `call_if` gates are checked once, against the context as the wave starts
+            let (wave, gated): (Vec<_>, Vec<_>) = wave
+                .into_iter()
+                .partition(|(layer, method)| slice.should_run(layer, method, &context));
+            for (layer_name, method_name) in gated {
+                results.mark_skipped(self.intern(layer_name), self.intern(method_name));
+            }
+
+            let successes = AtomicUsize::new(0);
+            let quorum_reached = |count: usize| slice.quorum.is_some_and(|quorum| count >= quorum);
+            context.set_wave_cancelled(false);
+
+            // `None` marks a method skipped because the wave already had its quorum
+            let run_pair = |&(layer_name, method_name): &(&str, &str)| {
+                let key = (self.intern(layer_name), self.intern(method_name));
+
+                if quorum_reached(successes.load(Ordering::SeqCst)) {
+                    return (key, None);
+                }
+
+                let (result, duration) = if use_observer {
+                    let (result, duration) = self.observe_execute_method(
+                        slice,
+                        layer_name,
+                        method_name,
+                        &context,
+                        state,
+                    );
+                    (result, Some(duration))
+                } else {
+                    let result =
+                        self.execute_method(slice, layer_name, method_name, &context, state);
+                    (result, None)
+                };
+
+                if result.is_ok() {
+                    let before = successes.fetch_add(1, Ordering::SeqCst);
+                    if quorum_reached(before) {
+                        return (key, None);
+                    }
+                    // Methods still running can see this and give up early
+                    if quorum_reached(before + 1) {
+                        context.set_wave_cancelled(true);
+                    }
+                } else if context.is_wave_cancelled() {
+                    return (key, None);
+                }
+
+                (key, Some((result, duration)))
+            };
+
+            let wave_results: Vec<_> = if self.config.sequential {
+                wave.iter().map(run_pair).collect()
+            } else {
+                wave.par_iter().map(run_pair).collect()
+            };
+
+            if use_observer {
+                self.observer.emit(EngineEvent::WaveComplete {
+                    run_id: state.run_id.clone(),
+                    slice: slice_name.clone(),
+                    wave_index,
+                    duration: wave_start.elapsed(),
+                });
+            }
 
-            for ((layer_name, method_name), result) in wave_results {
-                results.add_result(layer_name, method_name, result);
+            for ((layer_name, method_name), outcome) in wave_results {
+                match outcome {
+                    Some((result, duration)) => {
+                        if let Some(duration) = duration {
+                            results.set_method_duration(
+                                layer_name.clone(),
+                                method_name.clone(),
+                                duration,
+                            );
+                        }
+                        results.add_result(layer_name, method_name, result);
+                    }
+                    None => results.mark_skipped(layer_name, method_name),
+                }
             }
         }
 
+        if let Some(stats) = context.lock_stats() {
+            results.set_lock_stats(stats);
+        }
+
+        // Outputs of calls the quorum discarded aren't results
+        for (key, output) in context.take_typed_outputs() {
+            if matches!(results.method_results.get(&key), Some(Ok(_))) {
+                results.typed_results.insert(key, output);
+            }
+        }
+
+        if !slice.result_schema.is_empty() {
+            results.combine(&slice.result_schema);
+        }
+
         if use_observer {
             let duration = slice_start.elapsed();
             results.set_duration(duration);
 
             self.observer.emit(EngineEvent::SliceComplete {
+                run_id: state.run_id.clone(),
                 slice: slice_name,
+                slice_id: slice.get_id().to_string(),
                 duration: duration,
             });
         }
@@ -190,23 +633,103 @@ impl Engine {
         Ok(results)
     }
 
+    fn new_slice_context(&self, slice: &Slice, state: &RunState) -> crate::Result<Context> {
+        let mut context = match &self.emit_sink {
+            Some(sink) => Context::with_sink(slice.get_name(), sink.clone()),
+            None => Context::new(),
+        };
+        for (key, value) in self.context_seed(state)?.iter().chain(&slice.seed) {
+            context.set(key.clone(), value.clone());
+        }
+        if self.config.track_lock_contention {
+            context = context.with_lock_stats();
+        }
+        Ok(context)
+    }
+
+    // The shared context, plus whatever the once layer wrote. The once layer runs the
+    // first time any slice needs a context; its outcome (or failure) is kept for the
+    // lifetime of the engine.
+    fn context_seed(&self, state: &RunState) -> crate::Result<&HashMap<String, Value>> {
+        let Some(layer_name) = &self.once_layer else {
+            return Ok(&self.shared_context);
+        };
+
+        self.once_seed
+            .get_or_init(|| self.run_once_layer(layer_name, state))
+            .as_ref()
+            .map_err(|message| {
+                crate::Error::ExecutionError(format!(
+                    "Once layer '{}' failed: {}",
+                    layer_name, message
+                ))
+            })
+    }
+
+    // Methods go through execute_method like any other call, as a slice named after
+    // the layer, so the run's retry policy, panic handling, hooks and events apply
+    fn run_once_layer(
+        &self,
+        layer_name: &str,
+        state: &RunState,
+    ) -> std::result::Result<HashMap<String, Value>, String> {
+        let layer = self
+            .layers
+            .get(layer_name)
+            .ok_or_else(|| format!("Layer not found: {}", layer_name))?;
+
+        let context = Context::new();
+        for (key, value) in &self.shared_context {
+            context.set(key.clone(), value.clone());
+        }
+
+        let mut methods: Vec<&str> = layer.get_methods().into_iter().collect();
+        methods.sort();
+
+        let slice = methods
+            .iter()
+            .fold(Slice::builder(layer_name), |builder, method| {
+                builder.call(layer_name, *method, Value::Null)
+            })
+            .build();
+
+        for method in methods {
+            let result = if state.use_observer {
+                self.observe_execute_method(&slice, layer_name, method, &context, state)
+                    .0
+            } else {
+                self.execute_method(&slice, layer_name, method, &context, state)
+            };
+            result.map_err(|e| format!("{}: {}", method, e.message()))?;
+        }
+
+        Ok(context
+            .keys()
+            .into_iter()
+            .filter_map(|key| context.get(&key).map(|value| (key, value)))
+            .collect())
+    }
+
     fn observe_execute_method(
         &self,
         slice: &Slice,
         layer_name: &str,
         method_name: &str,
         ctx: &Context,
-    ) -> Result<Value> {
+        state: &RunState,
+    ) -> (Result<Value>, Duration) {
         let start = Instant::now();
         let slice_name = &slice.name;
 
         self.observer.emit(EngineEvent::MethodStart {
+            run_id: state.run_id.clone(),
             slice: slice_name.to_string(),
+            slice_id: slice.get_id().to_string(),
             layer: layer_name.to_string(),
             method: method_name.to_string(),
         });
 
-        let result = self.execute_method(slice, layer_name, method_name, &ctx);
+        let result = self.execute_method(slice, layer_name, method_name, ctx, state);
 
         let result = result.map_err(|e| {
             let args = slice
@@ -221,25 +744,31 @@ impl Engine {
             }
         });
 
+        let duration = start.elapsed();
+
         match &result {
             Ok(_) => {
                 self.observer.emit(EngineEvent::MethodComplete {
+                    run_id: state.run_id.clone(),
                     slice: slice_name.to_string(),
+                    slice_id: slice.get_id().to_string(),
                     layer: layer_name.to_string(),
                     method: method_name.to_string(),
-                    duration: start.elapsed(),
+                    duration,
                 });
             }
             Err(e) => {
                 self.observer.emit(EngineEvent::MethodFailed {
+                    run_id: state.run_id.clone(),
                     slice: slice_name.to_string(),
+                    slice_id: slice.get_id().to_string(),
                     layer: layer_name.to_string(),
                     method: method_name.to_string(),
                     error: e.to_string(),
                 });
             }
         }
-        result
+        (result, duration)
     }
 
     fn execute_method(
@@ -248,6 +777,7 @@ impl Engine {
         layer_name: &str,
         method_name: &str,
         ctx: &Context,
+        state: &RunState,
     ) -> Result<Value> {
         let layer = self
             .layers
@@ -256,76 +786,564 @@ impl Engine {
 
         let slice_args = slice.get_method_arg(layer_name, method_name)?;
 
-        if slice_args.is_null() {
-            layer.execute_with_default(method_name, ctx)
+        let context_args = if slice_args.is_null() {
+            layer
+                .get_context_default(method_name)
+                .and_then(|key| ctx.get(key))
+        } else {
+            None
+        };
+        let slice_args = context_args.as_ref().unwrap_or(slice_args);
+
+        // Args are only cloned when a merge actually builds a new value
+        let merged_args = if slice_args.is_null() {
+            None
+        } else if let Some(default_args) = layer.get_default_args(method_name) {
+            Some(self.merge_args(default_args, slice_args))
+        } else {
+            Some(Cow::Borrowed(slice_args))
+        };
+
+        // Env values override the layer defaults, but not args the slice sets itself
+        let merged_args = if state.env_args.is_empty() {
+            merged_args
         } else {
-            let merged_args = if let Some(default_args) = layer.get_default_args(method_name) {
-                Self::merge_args(default_args, slice_args)
+            let defaults = layer
+                .get_default_args(method_name)
+                .cloned()
+                .unwrap_or(Value::Null);
+            let base = Self::inject_env_args(&state.env_args, defaults);
+
+            Some(Cow::Owned(if slice_args.is_null() {
+                base
             } else {
-                slice_args.clone()
+                self.merge_args(&base, slice_args).into_owned()
+            }))
+        };
+
+        let permits = self.layer_permits.get(layer_name);
+
+        let call = || {
+            let _permit = permits.map(|permits| permits.acquire());
+            match &merged_args {
+                Some(args) => layer.execute(method_name, args, ctx),
+                None => layer.execute_with_default(method_name, ctx),
+            }
+        };
+
+        let invoke = || {
+            if !self.config.catch_panics {
+                return call();
+            }
+
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(call)).unwrap_or_else(|payload| {
+                let message = if let Some(msg) = payload.downcast_ref::<&str>() {
+                    msg.to_string()
+                } else if let Some(msg) = payload.downcast_ref::<String>() {
+                    msg.clone()
+                } else {
+                    "unknown panic payload".to_string()
+                };
+                Err(crate::Error::MethodPanicked { message })
+            })
+        };
+
+        // Hooks wrap the whole call: once before the first attempt and once after the last
+        for hook in &self.before_method {
+            hook(slice.get_name(), layer_name, method_name, ctx);
+        }
+
+        let result = self.invoke_with_retry(slice, layer_name, method_name, ctx, state, invoke);
+
+        for hook in &self.after_method {
+            hook(slice.get_name(), layer_name, method_name, &result, ctx);
+        }
+
+        result
+    }
+
+    fn invoke_with_retry<F>(
+        &self,
+        slice: &Slice,
+        layer_name: &str,
+        method_name: &str,
+        ctx: &Context,
+        state: &RunState,
+        invoke: F,
+    ) -> Result<Value>
+    where
+        F: Fn() -> Result<Value>,
+    {
+        let policy = match self.config.retry {
+            Some(policy) if policy.max_attempts > 1 => policy,
+            _ => return invoke(),
+        };
+
+        let mut attempt = 1;
+        loop {
+            let error = match invoke() {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
             };
 
-            layer.execute(method_name, &merged_args, ctx)
+            // A method that bailed because its wave was cancelled isn't worth retrying
+            if ctx.is_wave_cancelled() {
+                return Err(error);
+            }
+            if attempt >= policy.max_attempts {
+                return Err(crate::Error::RetriesExhausted {
+                    attempts: attempt,
+                    last: Box::new(error),
+                });
+            }
+
+            if state.use_observer {
+                self.observer.emit(EngineEvent::MethodRetry {
+                    run_id: state.run_id.clone(),
+                    slice: slice.get_name().to_string(),
+                    slice_id: slice.get_id().to_string(),
+                    layer: layer_name.to_string(),
+                    method: method_name.to_string(),
+                    attempt,
+                    error: error.to_string(),
+                });
+            }
+
+            std::thread::sleep(policy.backoff);
+            attempt += 1;
         }
     }
 
-    fn merge_args(defaults: &Value, overrides: &Value) -> Value {
+    // Null args (a default-less method called with `call_default`) start from an empty
+    // object; other non-object args can't take named values and are left alone
+    fn inject_env_args(env_args: &[(String, Value)], args: Value) -> Value {
+        let mut map = match args {
+            Value::Object(map) => map,
+            Value::Null => HashMap::new(),
+            other => return other,
+        };
+
+        for (key, value) in env_args {
+            map.insert(key.clone(), value.clone());
+        }
+        Value::Object(map)
+    }
+
+    fn merge_args<'a>(&self, defaults: &'a Value, overrides: &'a Value) -> Cow<'a, Value> {
+        if self.config.deep_merge_args && !overrides.is_null() {
+            let mut merged = defaults.clone();
+            merged.deep_merge(overrides);
+            return Cow::Owned(merged);
+        }
+
         match (defaults, overrides) {
+            // Merging with an empty side changes nothing
+            (Value::Object(def_map), Value::Object(_)) if def_map.is_empty() => {
+                Cow::Borrowed(overrides)
+            }
+            (Value::Object(_), Value::Object(over_map)) if over_map.is_empty() => {
+                Cow::Borrowed(defaults)
+            }
             (Value::Object(def_map), Value::Object(over_map)) => {
                 let mut merged = def_map.clone();
                 for (k, v) in over_map {
                     merged.insert(k.clone(), v.clone());
                 }
-                Value::Object(merged)
+                Cow::Owned(Value::Object(merged))
             }
-            (_, Value::Null) => defaults.clone(),
-            _ => overrides.clone(), // If override is not an object, just use it entirely
+            (_, Value::Null) => Cow::Borrowed(defaults),
+            _ => Cow::Borrowed(overrides), // If override is not an object, just use it entirely
         }
     }
 
     pub fn run(&self, flags: RunFlags) -> RunResults {
-        if flags.silent {
-            self.run_silent(flags.with_observer)
-        } else {
-            self.run_with_progress(flags.with_observer)
-        }
+        let execution_order = match self.topological_sort() {
+            Ok(order) => order,
+            Err(e) => panic!("Engine misconfigured: {}", e),
+        };
+
+        self.run_in_order(execution_order, self.slices.iter().collect(), flags, None)
     }
 
-    fn run_silent(&self, use_observer: bool) -> RunResults {
-        let pool = self.config.build_thread_pool().ok();
+    /// Like `run`, also reporting the batching, chunking and pool size the run used,
+    /// for correlating tuning experiments with their timings.
+    pub fn run_with_meta(&self, flags: RunFlags) -> (RunResults, RunMetadata) {
+        let start = Instant::now();
+        let results = self.run(flags);
+
+        let metadata = RunMetadata {
+            batch_size: self.config.batch_size,
+            chunk_size: self.config.chunk_size,
+            num_threads: self
+                .thread_pool()
+                .map_or_else(rayon::current_num_threads, |pool| {
+                    pool.current_num_threads()
+                }),
+            total_duration: start.elapsed(),
+        };
+
+        (results, metadata)
+    }
+
+    /// Like `run`, but stops once `token` is cancelled: slices that haven't started come
+    /// back as `Err(Error::Cancelled)`, and running slices stop before their next wave
+    /// (also as `Err(Error::Cancelled)`). Methods already executing finish normally.
+    pub fn run_with_cancel(&self, token: CancellationToken, flags: RunFlags) -> RunResults {
         let execution_order = match self.topological_sort() {
             Ok(order) => order,
             Err(e) => panic!("Engine misconfigured: {}", e),
         };
 
+        self.run_in_order(
+            execution_order,
+            self.slices.iter().collect(),
+            flags,
+            Some(token),
+        )
+    }
+
+    pub fn run_filtered<F>(&self, pred: F, flags: RunFlags) -> RunResults
+    where
+        F: Fn(&str) -> bool,
+    {
+        let execution_order = match self.topological_sort() {
+            Ok(order) => order,
+            Err(e) => panic!("Engine misconfigured: {}", e),
+        };
+
+        let slices = self
+            .slices
+            .iter()
+            .filter(|slice| pred(slice.get_name()))
+            .collect();
+
+        self.run_in_order(execution_order, slices, flags, None)
+    }
+
+    /// Runs again only the slices that errored, or had a failed method, in `prev`.
+    /// `RunResultsExt::merge` folds the new results over the old ones.
+    pub fn rerun_failed(&self, prev: &RunResults, flags: RunFlags) -> RunResults {
+        let failed: HashSet<&str> = prev
+            .get_slice_errors()
+            .into_iter()
+            .map(|(slice, _)| slice.as_str())
+            .chain(
+                prev.get_all_method_errors()
+                    .into_iter()
+                    .map(|(slice, _, _, _)| slice.as_str()),
+            )
+            .collect();
+
+        self.run_filtered(|name| failed.contains(name), flags)
+    }
+
+    /// Like `run`, but each slice's result is sent over the returned channel as soon as
+    /// it completes instead of being collected, so memory stays flat however many slices
+    /// there are. The channel holds `config.stream_capacity` results; once full, workers
+    /// block until the consumer catches up. The run happens on a background thread and
+    /// the channel closes when it's done. Progress output is skipped. Run events and
+    /// `on_run_end` hooks fire as usual, but see empty results, since every slice's
+    /// result was already sent.
+    pub fn run_streaming(
+        self: &Arc<Self>,
+        flags: RunFlags,
+    ) -> std::sync::mpsc::Receiver<(String, Result<SliceResults>)> {
+        use rayon::prelude::*;
+
+        let execution_order = match self.topological_sort() {
+            Ok(order) => order,
+            Err(e) => panic!("Engine misconfigured: {}", e),
+        };
+
+        let (sender, receiver) = std::sync::mpsc::sync_channel(self.config.stream_capacity);
+        let engine = Arc::clone(self);
+
+        std::thread::spawn(move || {
+            let state = engine.new_run_state(execution_order, flags);
+            let mut slices: Vec<&Slice> = engine.slices.iter().collect();
+            sort_by_priority(&mut slices);
+
+            // A closed receiver just means nobody is listening anymore
+            let task = |slice: &Slice| {
+                let _ = sender.send(engine.execute_slice_task(slice, &state));
+            };
+
+            let execute = || {
+                if engine.config.sequential {
+                    slices.iter().for_each(|slice| task(slice));
+                } else if let Some(lanes) = engine.config.max_concurrent_slices {
+                    run_in_lanes(&slices, lanes, task);
+                } else {
+                    slices.par_iter().for_each(|slice| task(slice));
+                }
+            };
+
+            engine.observe_run(&state, slices.len(), || {
+                match engine.thread_pool() {
+                    Some(pool) => pool.install(execute),
+                    None => execute(),
+                }
+                RunResults::new()
+            });
+        });
+
+        receiver
+    }
+
+    /// Runs slices pulled lazily from `slices`, so they never all exist in memory at
+    /// once. They're taken `batch_size` at a time (1024 if unset), and each batch is
+    /// dropped before the next is produced; only the results are kept.
+    /// The total isn't known upfront, so there's no progress display, regardless of
+    /// `flags.silent`, and `RunStart` reports the iterator's lower size bound. Slice
+    /// priorities only reorder slices within a batch.
+    pub fn run_iter<I>(&self, slices: I, flags: RunFlags) -> RunResults
+    where
+        I: IntoIterator<Item = Slice>,
+    {
+        let execution_order = match self.topological_sort() {
+            Ok(order) => order,
+            Err(e) => panic!("Engine misconfigured: {}", e),
+        };
+
+        let state = self.new_run_state(execution_order, flags);
+        let batch_size = self.config.batch_size.unwrap_or(1024).max(1);
+        let mut slices = slices.into_iter();
+
+        self.observe_run(&state, slices.size_hint().0, || {
+            let pool = self.thread_pool();
+            let mut results = HashMap::new();
+
+            loop {
+                let batch: Vec<Slice> = slices.by_ref().take(batch_size).collect();
+                if batch.is_empty() {
+                    break;
+                }
+
+                let mut batch: Vec<&Slice> = batch.iter().collect();
+                sort_by_priority(&mut batch);
+                results.extend(self.execute_batch_silent(&batch, &state, &pool));
+            }
+
+            RunResults::from(results)
+        })
+    }
+
+    pub fn run_init_only(&self, flags: RunFlags) -> crate::Result<RunResults> {
+        let init_name = self.init_layer.as_ref().ok_or_else(|| {
+            crate::Error::ConfigError("No init layer configured for this engine".to_string())
+        })?;
+
+        Ok(self.run_in_order(
+            vec![init_name.clone()],
+            self.slices.iter().collect(),
+            flags,
+            None,
+        ))
+    }
+
+    /// Re-invokes every failed method in `results` and overwrites its entry when the
+    /// retry succeeds, returning how many were recovered. Each retry gets a fresh context
+    /// holding only the shared context, so only methods that don't depend on context from
+    /// earlier waves recover.
+    /// Slices that failed as a whole are left alone.
+    pub fn retry_failures(&self, results: &mut RunResults, flags: RunFlags) -> usize {
+        use rayon::prelude::*;
+
+        let state = self.new_run_state(Vec::new(), flags);
+
+        let failed: Vec<(&Slice, Arc<str>, Arc<str>)> = self
+            .slices
+            .iter()
+            .filter_map(|slice| match results.get(slice.get_name()) {
+                Some(Ok(slice_results)) => Some((slice, slice_results)),
+                _ => None,
+            })
+            .flat_map(|(slice, slice_results)| {
+                slice_results
+                    .method_results
+                    .iter()
+                    .filter(|(_, result)| result.is_err())
+                    .map(move |((layer, method), _)| (slice, layer.clone(), method.clone()))
+            })
+            .collect();
+
+        let retry_one = |(slice, layer, method): &(&Slice, Arc<str>, Arc<str>)| {
+            let result = self.new_slice_context(slice, &state).and_then(|context| {
+                if state.use_observer {
+                    self.observe_execute_method(slice, layer, method, &context, &state)
+                        .0
+                } else {
+                    self.execute_method(slice, layer, method, &context, &state)
+                }
+            });
+            (
+                slice.get_name().to_string(),
+                layer.clone(),
+                method.clone(),
+                result,
+            )
+        };
+
+        let retry = || -> Vec<_> {
+            if self.config.sequential {
+                failed.iter().map(retry_one).collect()
+            } else {
+                failed.par_iter().map(retry_one).collect()
+            }
+        };
+
+        let retried = match self.thread_pool() {
+            Some(pool) => pool.install(retry),
+            None => retry(),
+        };
+
+        let mut recovered = 0;
+        for (slice_name, layer, method, result) in retried {
+            if let (Ok(value), Some(Ok(slice_results))) = (result, results.get_mut(&slice_name)) {
+                slice_results.add_result(layer, method, Ok(value));
+                recovered += 1;
+            }
+        }
+
+        recovered
+    }
+
+    // Falls back to rayon's global pool if the configured one can't be built
+    fn thread_pool(&self) -> Option<Arc<rayon::ThreadPool>> {
+        let settings = self.config.pool_settings();
+        let mut cached = self.pool.lock().unwrap();
+
+        if let Some(cached) = cached.as_ref()
+            && cached.settings == settings
+        {
+            return Some(cached.pool.clone());
+        }
+
+        let pool = Arc::new(self.config.build_thread_pool().ok()?);
+        *cached = Some(CachedPool {
+            settings,
+            pool: pool.clone(),
+        });
+        Some(pool)
+    }
+
+    pub fn run_slice(&self, name: &str, flags: RunFlags) -> crate::Result<SliceResults> {
+        let slice = self
+            .slices
+            .iter()
+            .find(|slice| slice.get_name() == name)
+            .ok_or_else(|| crate::Error::ConfigError(format!("No slice named '{}'", name)))?;
+
+        let execution_order = self.topological_sort()?;
+        let state = self.new_run_state(execution_order, flags);
+
+        match self.thread_pool() {
+            Some(pool) => pool.install(|| self.execute_slice(slice, &state)),
+            None => self.execute_slice(slice, &state),
+        }
+    }
+
+    fn new_run_state(&self, execution_order: Vec<String>, flags: RunFlags) -> RunState {
+        let run_id = self.config.run_id.clone().unwrap_or_else(generate_run_id);
+        let mut state = RunState::new(run_id, execution_order, flags);
+
+        // Unset variables are skipped, leaving the key to defaults and slice args
+        state.env_args = self
+            .env_args
+            .iter()
+            .filter_map(|(key, var)| {
+                std::env::var(var)
+                    .ok()
+                    .map(|value| (key.clone(), Value::from(value)))
+            })
+            .collect();
+
+        state
+    }
+
+    fn run_in_order(
+        &self,
+        execution_order: Vec<String>,
+        mut slices: Vec<&Slice>,
+        flags: RunFlags,
+        cancel: Option<CancellationToken>,
+    ) -> RunResults {
+        sort_by_priority(&mut slices);
+        let mut state = self.new_run_state(execution_order, flags);
+        state.cancel = cancel;
+
+        self.observe_run(&state, slices.len(), || {
+            if flags.silent {
+                self.run_silent(&slices, &state)
+            } else {
+                self.run_with_progress(&slices, &state)
+            }
+        })
+    }
+
+    // Run-level events and run-end hooks around a whole run
+    fn observe_run<F>(&self, state: &RunState, total_slices: usize, run: F) -> RunResults
+    where
+        F: FnOnce() -> RunResults,
+    {
+        let guard = RunEndGuard {
+            hooks: &self.run_end,
+            finished: false,
+        };
+
+        let start = Instant::now();
+        if state.use_observer {
+            self.observer.emit(EngineEvent::RunStart {
+                run_id: state.run_id.clone(),
+                total_slices,
+            });
+        }
+
+        let results = run();
+
+        if state.use_observer {
+            self.observer.emit(EngineEvent::RunComplete {
+                run_id: state.run_id.clone(),
+                duration: start.elapsed(),
+                succeeded: results.successful_slices(),
+                failed: results.failed_slices(),
+            });
+        }
+
+        guard.finish(&results);
+        results
+    }
+
+    fn run_silent(&self, slices: &[&Slice], state: &RunState) -> RunResults {
+        let pool = self.thread_pool();
+
         // Check if we need batched execution (for memory management)
         let intermediary = if let Some(batch_size) = self.config.batch_size {
             // Process in batches to prevent memory exhaustion
             let mut all_results = HashMap::new();
 
-            for batch in self.slices.chunks(batch_size) {
-                let batch_results =
-                    self.execute_batch_silent(batch, &execution_order, &pool, use_observer);
+            for batch in slices.chunks(batch_size) {
+                let batch_results = self.execute_batch_silent(batch, state, &pool);
                 all_results.extend(batch_results);
             }
 
             all_results
         } else {
             // Process all slices at once
-            self.execute_batch_silent(&self.slices, &execution_order, &pool, use_observer)
+            self.execute_batch_silent(slices, state, &pool)
         };
 
         RunResults::from(intermediary)
     }
 
-    fn run_with_progress(&self, use_observer: bool) -> RunResults {
-        let execution_order = match self.topological_sort() {
-            Ok(order) => order,
-            Err(e) => panic!("Engine misconfigured: {}", e),
-        };
-
-        let pool = self.config.build_thread_pool().ok();
-        let tracker = Arc::new(ProgressTracker::new(self.slices.len()));
+    fn run_with_progress(&self, slices: &[&Slice], state: &RunState) -> RunResults {
+        let pool = self.thread_pool();
+        let tracker = Arc::new(
+            ProgressTracker::new(slices.len())
+                .verbose_summary(self.config.verbose_summary)
+                .writer(self.config.progress_writer.clone())
+                .interval(self.config.progress_interval),
+        );
         tracker.print_header();
 
         // Check if we need batched execution (for memory management)
@@ -333,27 +1351,15 @@ impl Engine {
             // Process in batches with progress tracking
             let mut all_results = HashMap::new();
 
-            for batch in self.slices.chunks(batch_size) {
-                let batch_results = self.execute_batch_with_progress(
-                    batch,
-                    &execution_order,
-                    &pool,
-                    &tracker,
-                    use_observer,
-                );
+            for batch in slices.chunks(batch_size) {
+                let batch_results = self.execute_batch_with_progress(batch, state, &pool, &tracker);
                 all_results.extend(batch_results);
             }
 
             all_results
         } else {
             // Process all slices at once with progress
-            self.execute_batch_with_progress(
-                &self.slices,
-                &execution_order,
-                &pool,
-                &tracker,
-                use_observer,
-            )
+            self.execute_batch_with_progress(slices, state, &pool, &tracker)
         };
 
         let results = RunResults::from(intermediary);
@@ -363,29 +1369,32 @@ impl Engine {
 
     fn execute_batch_silent(
         &self,
-        slices: &[Slice],
-        execution_order: &[String],
-        pool: &Option<rayon::ThreadPool>,
-        use_observer: bool,
+        slices: &[&Slice],
+        state: &RunState,
+        pool: &Option<Arc<rayon::ThreadPool>>,
     ) -> HashMap<String, Result<SliceResults>> {
         use rayon::prelude::*;
 
         let chunk_size = self.config.chunk_size;
 
         let execute = || {
-            if chunk_size > 1 {
+            if self.config.sequential {
+                slices
+                    .iter()
+                    .map(|slice| self.execute_slice_task(slice, state))
+                    .collect()
+            } else if let Some(lanes) = self.config.max_concurrent_slices {
+                run_in_lanes(slices, lanes, |slice| self.execute_slice_task(slice, state))
+                    .into_iter()
+                    .collect()
+            } else if chunk_size > 1 {
                 // Use chunking to reduce thread coordination overhead
                 slices
                     .par_chunks(chunk_size)
                     .flat_map(|chunk| {
                         chunk
                             .iter()
-                            .map(|slice| {
-                                let slice_name = slice.get_name().to_string();
-                                let result =
-                                    self.execute_slice(slice, execution_order, use_observer);
-                                (slice_name, result)
-                            })
+                            .map(|slice| self.execute_slice_task(slice, state))
                             .collect::<Vec<_>>()
                     })
                     .collect()
@@ -393,11 +1402,7 @@ impl Engine {
                 // No chunking - one item per coordination
                 slices
                     .par_iter()
-                    .map(|slice| {
-                        let slice_name = slice.get_name().to_string();
-                        let result = self.execute_slice(slice, execution_order, use_observer);
-                        (slice_name, result)
-                    })
+                    .map(|slice| self.execute_slice_task(slice, state))
                     .collect()
             }
         };
@@ -411,39 +1416,48 @@ impl Engine {
 
     fn execute_batch_with_progress(
         &self,
-        slices: &[Slice],
-        execution_order: &[String],
-        pool: &Option<rayon::ThreadPool>,
+        slices: &[&Slice],
+        state: &RunState,
+        pool: &Option<Arc<rayon::ThreadPool>>,
         tracker: &Arc<ProgressTracker>,
-        use_observer: bool,
     ) -> HashMap<String, Result<SliceResults>> {
         use rayon::prelude::*;
 
         let chunk_size = self.config.chunk_size;
+        let use_observer = state.use_observer;
+
+        let track = |(slice_name, result): (String, Result<SliceResults>)| {
+            // Update progress if observer is enabled
+            if use_observer {
+                match &result {
+                    Ok(_) => tracker.increment_completed(),
+                    Err(_) => tracker.increment_failed(),
+                }
+            }
+
+            (slice_name, result)
+        };
 
         let execute = || {
-            if chunk_size > 1 {
+            if self.config.sequential {
+                slices
+                    .iter()
+                    .map(|slice| track(self.execute_slice_task(slice, state)))
+                    .collect()
+            } else if let Some(lanes) = self.config.max_concurrent_slices {
+                run_in_lanes(slices, lanes, |slice| {
+                    track(self.execute_slice_task(slice, state))
+                })
+                .into_iter()
+                .collect()
+            } else if chunk_size > 1 {
                 // Use chunking to reduce thread coordination overhead
                 slices
                     .par_chunks(chunk_size)
                     .flat_map(|chunk| {
                         chunk
                             .iter()
-                            .map(|slice| {
-                                let slice_name = slice.get_name().to_string();
-                                let result =
-                                    self.execute_slice(slice, execution_order, use_observer);
-
-                                // Update progress if observer is enabled
-                                if use_observer {
-                                    match &result {
-                                        Ok(_) => tracker.increment_completed(),
-                                        Err(_) => tracker.increment_failed(),
-                                    }
-                                }
-
-                                (slice_name, result)
-                            })
+                            .map(|slice| track(self.execute_slice_task(slice, state)))
                             .collect::<Vec<_>>()
                     })
                     .collect()
@@ -451,20 +1465,7 @@ impl Engine {
                 // No chunking - one item per coordination
                 slices
                     .par_iter()
-                    .map(|slice| {
-                        let slice_name = slice.get_name().to_string();
-                        let result = self.execute_slice(slice, execution_order, use_observer);
-
-                        // Update progress if observer is enabled
-                        if use_observer {
-                            match &result {
-                                Ok(_) => tracker.increment_completed(),
-                                Err(_) => tracker.increment_failed(),
-                            }
-                        }
-
-                        (slice_name, result)
-                    })
+                    .map(|slice| track(self.execute_slice_task(slice, state)))
                     .collect()
             }
         };
@@ -476,6 +1477,152 @@ impl Engine {
         }
     }
 
+    fn execute_slice_task(
+        &self,
+        slice: &Slice,
+        state: &RunState,
+    ) -> (String, Result<SliceResults>) {
+        let slice_name = slice.get_name().to_string();
+
+        if state.is_halted() {
+            return (slice_name, Err(crate::Error::Cancelled));
+        }
+
+        let result = self.execute_slice(slice, state);
+
+        if state.fail_fast && Self::has_any_failure(&result) {
+            state.halt();
+        }
+
+        (slice_name, result)
+    }
+
+    fn has_any_failure(result: &Result<SliceResults>) -> bool {
+        match result {
+            Ok(slice_results) => slice_results.method_results.values().any(|r| r.is_err()),
+            Err(_) => true,
+        }
+    }
+
+    pub fn validate(&self) -> crate::Result<()> {
+        // Both ends of every edge must be registered layers, even on layers no slice calls
+        let mut missing: Vec<&String> = self
+            .dependencies
+            .iter()
+            .flat_map(|(layer, deps)| std::iter::once(layer).chain(deps))
+            .chain(
+                self.conditional_dependencies
+                    .iter()
+                    .flat_map(|(layer, deps)| {
+                        std::iter::once(layer).chain(deps.iter().map(|(dep, _)| dep))
+                    }),
+            )
+            .filter(|dep| !self.layers.contains_key(*dep))
+            .collect();
+        missing.sort();
+        if let Some(dep) = missing.first() {
+            return Err(crate::Error::LayerNotFound((*dep).clone()));
+        }
+
+        for slice in &self.slices {
+            let mut layer_names = slice.get_layer_names()?;
+            layer_names.sort();
+
+            for layer_name in layer_names {
+                let mut methods = slice.get_layer_methods(layer_name)?;
+                methods.sort();
+
+                let Some(layer) = self.layers.get(layer_name) else {
+                    let reference = match methods.first() {
+                        Some(method) => format!("{}.{}.{}", slice.get_name(), layer_name, method),
+                        None => format!("{}.{}", slice.get_name(), layer_name),
+                    };
+                    return Err(crate::Error::ConfigError(format!(
+                        "Unknown layer referenced by {}",
+                        reference
+                    )));
+                };
+
+                if let Some(method) = methods
+                    .iter()
+                    .find(|method| layer.get_default_args(method).is_none())
+                {
+                    return Err(crate::Error::ConfigError(format!(
+                        "Unknown method referenced by {}.{}.{}",
+                        slice.get_name(),
+                        layer_name,
+                        method
+                    )));
+                }
+            }
+
+            if let Some(once) = &self.once_layer
+                && slice.has_layer(once)
+            {
+                return Err(crate::Error::ConfigError(format!(
+                    "Slice '{}' calls once layer '{}', which runs outside of slices",
+                    slice.get_name(),
+                    once
+                )));
+            }
+
+            for group in &self.exclusive_groups {
+                let used: Vec<&str> = group
+                    .iter()
+                    .filter(|layer| slice.has_layer(layer))
+                    .map(|layer| layer.as_str())
+                    .collect();
+
+                if used.len() > 1 {
+                    return Err(crate::Error::ConfigError(format!(
+                        "Slice '{}' uses mutually exclusive layers: {}",
+                        slice.get_name(),
+                        used.join(", ")
+                    )));
+                }
+            }
+
+            for field in &slice.result_schema {
+                if slice.get_method_arg(&field.layer, &field.method).is_err() {
+                    return Err(crate::Error::ConfigError(format!(
+                        "Result field '{}' references {}.{}.{}, which the slice doesn't call",
+                        field.field,
+                        slice.get_name(),
+                        field.layer,
+                        field.method
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Lists every layer, sorted by name, followed by one indented line per method with
+    /// its default args and description, e.g. `  download {"retries":3} - Downloads one page`.
+    pub fn describe(&self) -> String {
+        let mut layers: Vec<&Layer> = self.layers.values().collect();
+        layers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut out = String::new();
+        for layer in layers {
+            out.push_str(&layer.name);
+            out.push('\n');
+
+            let mut methods: Vec<(&String, &Value)> = layer.methods_to_defaults.iter().collect();
+            methods.sort_by(|a, b| a.0.cmp(b.0));
+
+            for (method, defaults) in methods {
+                out.push_str(&format!("  {} {}", method, defaults));
+                if let Some(description) = layer.method_description(method) {
+                    out.push_str(&format!(" - {}", description));
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+
     pub fn set_observer(&mut self, observer: Observer) {
         self.observer = observer;
     }
@@ -484,6 +1631,89 @@ impl Engine {
         &mut self.observer
     }
 
+    pub fn set_env_args(&mut self, mapping: Vec<(String, String)>) {
+        self.env_args = mapping;
+    }
+
+    pub fn add_exclusive_group(&mut self, layers: Vec<String>) {
+        self.exclusive_groups.push(layers);
+    }
+
+    pub fn add_before_method(&mut self, hook: BeforeMethodHook) {
+        self.before_method.push(hook);
+    }
+
+    pub fn add_after_method(&mut self, hook: AfterMethodHook) {
+        self.after_method.push(hook);
+    }
+
+    /// Seed copied into every slice's context before its first wave.
+    pub fn set_shared_context(&mut self, entries: HashMap<String, Value>) {
+        self.shared_context = entries;
+    }
+
+    pub fn add_run_end_hook(&mut self, hook: RunEndHook) {
+        self.run_end.push(hook);
+    }
+
+    pub fn set_emit_sink(&mut self, sink: EmitSink) {
+        self.emit_sink = Some(sink);
+    }
+
+    /// GraphViz `digraph` of the layers, with an edge `dep -> layer` per dependency.
+    /// Conditional edges are dashed and the init layer is drawn as a box.
+    pub fn to_dot(&self) -> String {
+        fn quote(name: &str) -> String {
+            format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+
+        let mut layers: Vec<&String> = self.layers.keys().collect();
+        layers.sort();
+
+        let mut edges: Vec<(&String, &String, bool)> = Vec::new();
+        for (layer, deps) in &self.dependencies {
+            edges.extend(deps.iter().map(|dep| (dep, layer, false)));
+        }
+        for (layer, deps) in &self.conditional_dependencies {
+            edges.extend(deps.iter().map(|(dep, _)| (dep, layer, true)));
+        }
+        edges.sort();
+        edges.dedup();
+
+        let mut dot = String::from("digraph sandl {\n");
+        for layer in layers {
+            if self.init_layer.as_ref() == Some(layer) {
+                dot.push_str(&format!("    {} [shape=box];\n", quote(layer)));
+            } else {
+                dot.push_str(&format!("    {};\n", quote(layer)));
+            }
+        }
+        for (dep, layer, conditional) in edges {
+            let style = if conditional { " [style=dashed]" } else { "" };
+            dot.push_str(&format!(
+                "    {} -> {}{};\n",
+                quote(dep),
+                quote(layer),
+                style
+            ));
+        }
+        dot.push('}');
+        dot.push('\n');
+
+        dot
+    }
+
+    /// Marks a layer whose methods run a single time per engine, before the first slice,
+    /// with their context writes seeding every slice's context. Slices can't call it.
+    pub fn set_once_layer(&mut self, layer_name: &str) -> crate::Result<()> {
+        if !self.layers.contains_key(layer_name) {
+            return Err(crate::Error::LayerNotFound(layer_name.to_string()));
+        }
+
+        self.once_layer = Some(layer_name.to_string());
+        Ok(())
+    }
+
     pub fn set_init_layer(&mut self, layer_name: &str) -> crate::Result<()> {
         if !self.layers.contains_key(layer_name) {
             return Err(crate::Error::LayerNotFound(layer_name.to_string()));
@@ -493,11 +1723,28 @@ impl Engine {
         Ok(())
     }
 
+    /// Adding an edge that already exists is a no-op.
     pub fn add_dependency(&mut self, layer: &str, depends_on: &str) -> crate::Result<()> {
-        self.dependencies
+        let deps = self
+            .dependencies
+            .entry(layer.to_string())
+            .or_insert_with(Vec::new);
+        if !deps.iter().any(|dep| dep == depends_on) {
+            deps.push(depends_on.to_string());
+        }
+        Ok(())
+    }
+
+    pub fn add_conditional_dependency(
+        &mut self,
+        layer: &str,
+        depends_on: &str,
+        predicate: DependencyPredicate,
+    ) -> crate::Result<()> {
+        self.conditional_dependencies
             .entry(layer.to_string())
-            .or_insert_with(Vec::new)
-            .push(depends_on.to_string());
+            .or_default()
+            .push((depends_on.to_string(), predicate));
         Ok(())
     }
 
@@ -510,10 +1757,26 @@ impl Engine {
         if self.layers.contains_key(&name) {
             return Err(crate::Error::LayerAlreadyExists(name));
         }
+        if let Some(limit) = layer.max_concurrency {
+            self.layer_permits
+                .insert(name.clone(), Arc::new(Semaphore::new(limit)));
+        }
+        for method in layer.methods_to_defaults.keys().chain(layer.binds.keys()) {
+            self.names
+                .entry(method.clone())
+                .or_insert_with(|| method.as_str().into());
+        }
+        self.names.insert(name.clone(), name.as_str().into());
         self.layers.insert(name, layer);
         Ok(())
     }
 
+    // Every registered name is interned, so the fallback only guards against a name
+    // that bypassed validation
+    fn intern(&self, name: &str) -> Arc<str> {
+        self.names.get(name).cloned().unwrap_or_else(|| name.into())
+    }
+
     pub fn get_layer_names(&self) -> Vec<String> {
         self.layers.keys().map(|s| s.to_string()).collect()
     }
@@ -529,3 +1792,78 @@ impl Engine {
         self.dependencies.get(layer)
     }
 }
+
+// Fires the run-end hooks exactly once: with the results on the normal path, or
+// with empty results while unwinding if the run panicked.
+struct RunEndGuard<'a> {
+    hooks: &'a [RunEndHook],
+    finished: bool,
+}
+
+impl RunEndGuard<'_> {
+    fn finish(mut self, results: &RunResults) {
+        self.finished = true;
+        for hook in self.hooks {
+            hook(results);
+        }
+    }
+}
+
+impl Drop for RunEndGuard<'_> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        // A second panic while unwinding would abort, so each hook is contained
+        let empty = RunResults::new();
+        for hook in self.hooks {
+            let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| hook(&empty)));
+        }
+    }
+}
+
+// Highest priority first. The sort is stable, so equal priorities keep their order
+fn sort_by_priority(slices: &mut [&Slice]) {
+    slices.sort_by_key(|slice| std::cmp::Reverse(slice.priority));
+}
+
+// Caps in-flight slices by running `lanes` parallel loops that each claim the next
+// slice off a shared counter. Blocking rayon workers on a semaphore instead could
+// deadlock: a worker waiting inside a wave's join may steal a slice task and park
+// on the semaphore while its own slice still holds a permit.
+fn run_in_lanes<T, F>(slices: &[&Slice], lanes: usize, task: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&Slice) -> T + Sync,
+{
+    use rayon::prelude::*;
+
+    let next = AtomicUsize::new(0);
+
+    (0..lanes.clamp(1, slices.len().max(1)))
+        .into_par_iter()
+        .flat_map_iter(|_| {
+            let mut out = Vec::new();
+            while let Some(slice) = slices.get(next.fetch_add(1, Ordering::Relaxed)) {
+                out.push(task(slice));
+            }
+            out
+        })
+        .collect()
+}
+
+fn generate_run_id() -> String {
+    // Random (version 4) UUID layout
+    let bits: u128 = rand::random();
+    let bits = (bits & !(0xF << 76) & !(0x3 << 62)) | (0x4 << 76) | (0x2 << 62);
+
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        (bits >> 96) as u32,
+        (bits >> 80) as u16,
+        (bits >> 64) as u16,
+        (bits >> 48) as u16,
+        bits & 0xFFFF_FFFF_FFFF
+    )
+}
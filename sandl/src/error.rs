@@ -26,14 +26,46 @@ pub enum Error {
         cause: Box<Error>,
     },
 
+    #[error("Retries exhausted after {attempts} attempts: {last}")]
+    RetriesExhausted {
+        attempts: usize,
+        #[source]
+        last: Box<Error>,
+    },
+
+    #[error("Method panicked: {message}")]
+    MethodPanicked { message: String },
+
     #[error("Execution error: {0}")]
     ExecutionError(String),
 
+    /// Like `ExecutionError`, but keeps the original error as the source, so it can be
+    /// downcast back.
+    #[error("{message}")]
+    Wrapped {
+        message: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
     #[error("Configuration error: {0}")]
     ConfigError(String),
+
+    #[error("Slice was cancelled")]
+    Cancelled,
 }
 
 impl Error {
+    pub fn wrap(
+        message: impl Into<String>,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Error::Wrapped {
+            message: message.into(),
+            source: source.into(),
+        }
+    }
+
     pub fn with_context(
         self,
         slice: impl Into<String>,
@@ -53,6 +85,7 @@ impl Error {
     pub fn root_cause(&self) -> &Error {
         match self {
             Error::MethodExecutionFailed { cause, .. } => cause.root_cause(),
+            Error::RetriesExhausted { last, .. } => last.root_cause(),
             other => other,
         }
     }
@@ -78,7 +111,27 @@ impl Error {
         match self {
             Error::MethodExecutionFailed { cause, .. } => cause.message(),
             Error::ExecutionError(msg) => msg.clone(),
+            Error::Wrapped { message, source } => format!("{}: {}", message, source),
             other => other.to_string(),
         }
     }
 }
+
+// So bound methods can use `?` on std fallible calls
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::ExecutionError(err.to_string())
+    }
+}
+
+impl From<std::num::ParseIntError> for Error {
+    fn from(err: std::num::ParseIntError) -> Self {
+        Error::ExecutionError(err.to_string())
+    }
+}
+
+impl From<std::num::ParseFloatError> for Error {
+    fn from(err: std::num::ParseFloatError) -> Self {
+        Error::ExecutionError(err.to_string())
+    }
+}
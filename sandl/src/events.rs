@@ -1,39 +1,118 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub enum EngineEvent {
+    RunStart {
+        run_id: String,
+        total_slices: usize,
+    },
+    RunComplete {
+        run_id: String,
+        duration: Duration,
+        succeeded: usize,
+        failed: usize,
+    },
+
     SliceStart {
+        run_id: String,
         slice: String,
+        slice_id: String,
     },
     SliceComplete {
+        run_id: String,
         slice: String,
+        slice_id: String,
         duration: Duration,
     },
     SliceFailed {
+        run_id: String,
         slice: String,
+        slice_id: String,
         error: String,
     },
 
+    WaveStart {
+        run_id: String,
+        slice: String,
+        wave_index: usize,
+        size: usize,
+    },
+    WaveComplete {
+        run_id: String,
+        slice: String,
+        wave_index: usize,
+        duration: Duration,
+    },
+
     MethodStart {
+        run_id: String,
         slice: String,
+        slice_id: String,
         layer: String,
         method: String,
     },
     MethodComplete {
+        run_id: String,
         slice: String,
+        slice_id: String,
         layer: String,
         method: String,
         duration: Duration,
     },
     MethodFailed {
+        run_id: String,
+        slice: String,
+        slice_id: String,
+        layer: String,
+        method: String,
+        error: String,
+    },
+    MethodRetry {
+        run_id: String,
         slice: String,
+        slice_id: String,
         layer: String,
         method: String,
+        attempt: usize,
         error: String,
     },
 }
 
+impl EngineEvent {
+    pub fn run_id(&self) -> &str {
+        match self {
+            EngineEvent::RunStart { run_id, .. }
+            | EngineEvent::RunComplete { run_id, .. }
+            | EngineEvent::SliceStart { run_id, .. }
+            | EngineEvent::SliceComplete { run_id, .. }
+            | EngineEvent::SliceFailed { run_id, .. }
+            | EngineEvent::WaveStart { run_id, .. }
+            | EngineEvent::WaveComplete { run_id, .. }
+            | EngineEvent::MethodStart { run_id, .. }
+            | EngineEvent::MethodComplete { run_id, .. }
+            | EngineEvent::MethodFailed { run_id, .. }
+            | EngineEvent::MethodRetry { run_id, .. } => run_id,
+        }
+    }
+
+    /// The slice's correlation id, on slice and method events.
+    pub fn slice_id(&self) -> Option<&str> {
+        match self {
+            EngineEvent::SliceStart { slice_id, .. }
+            | EngineEvent::SliceComplete { slice_id, .. }
+            | EngineEvent::SliceFailed { slice_id, .. }
+            | EngineEvent::MethodStart { slice_id, .. }
+            | EngineEvent::MethodComplete { slice_id, .. }
+            | EngineEvent::MethodFailed { slice_id, .. }
+            | EngineEvent::MethodRetry { slice_id, .. } => Some(slice_id),
+            _ => None,
+        }
+    }
+}
+
 pub type EventCallback = Arc<dyn Fn(&EngineEvent) + Send + Sync>;
 
 #[derive(Clone)]
@@ -60,6 +139,14 @@ impl Observer {
             callback(&event);
         }
     }
+
+    pub fn clear(&mut self) {
+        self.callbacks.clear();
+    }
+
+    pub fn callback_count(&self) -> usize {
+        self.callbacks.len()
+    }
 }
 
 impl Default for Observer {
@@ -69,12 +156,41 @@ impl Default for Observer {
 }
 
 impl Observer {
+    pub fn on_run_start<F>(&mut self, f: F)
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        self.on_event(move |event| {
+            if let EngineEvent::RunStart { total_slices, .. } = event {
+                f(*total_slices);
+            }
+        });
+    }
+
+    /// Called with the run's duration and its succeeded and failed slice counts.
+    pub fn on_run_complete<F>(&mut self, f: F)
+    where
+        F: Fn(Duration, usize, usize) + Send + Sync + 'static,
+    {
+        self.on_event(move |event| {
+            if let EngineEvent::RunComplete {
+                duration,
+                succeeded,
+                failed,
+                ..
+            } = event
+            {
+                f(*duration, *succeeded, *failed);
+            }
+        });
+    }
+
     pub fn on_slice_start<F>(&mut self, f: F)
     where
         F: Fn(&str) + Send + Sync + 'static,
     {
         self.on_event(move |event| {
-            if let EngineEvent::SliceStart { slice } = event {
+            if let EngineEvent::SliceStart { slice, .. } = event {
                 f(slice);
             }
         });
@@ -85,12 +201,61 @@ impl Observer {
         F: Fn(&str, Duration) + Send + Sync + 'static,
     {
         self.on_event(move |event| {
-            if let EngineEvent::SliceComplete { slice, duration } = event {
+            if let EngineEvent::SliceComplete {
+                slice, duration, ..
+            } = event
+            {
                 f(slice, *duration);
             }
         });
     }
 
+    pub fn on_slice_failed<F>(&mut self, f: F)
+    where
+        F: Fn(&str, &str) + Send + Sync + 'static,
+    {
+        self.on_event(move |event| {
+            if let EngineEvent::SliceFailed { slice, error, .. } = event {
+                f(slice, error);
+            }
+        });
+    }
+
+    /// Called with the slice, the wave's index and its number of (layer, method) calls.
+    pub fn on_wave_start<F>(&mut self, f: F)
+    where
+        F: Fn(&str, usize, usize) + Send + Sync + 'static,
+    {
+        self.on_event(move |event| {
+            if let EngineEvent::WaveStart {
+                slice,
+                wave_index,
+                size,
+                ..
+            } = event
+            {
+                f(slice, *wave_index, *size);
+            }
+        });
+    }
+
+    pub fn on_wave_complete<F>(&mut self, f: F)
+    where
+        F: Fn(&str, usize, Duration) + Send + Sync + 'static,
+    {
+        self.on_event(move |event| {
+            if let EngineEvent::WaveComplete {
+                slice,
+                wave_index,
+                duration,
+                ..
+            } = event
+            {
+                f(slice, *wave_index, *duration);
+            }
+        });
+    }
+
     pub fn on_method_start<F>(&mut self, f: F)
     where
         F: Fn(&str, &str, &str) + Send + Sync + 'static,
@@ -100,6 +265,7 @@ impl Observer {
                 slice,
                 layer,
                 method,
+                ..
             } = event
             {
                 f(slice, layer, method);
@@ -117,6 +283,7 @@ impl Observer {
                 layer,
                 method,
                 duration,
+                ..
             } = event
             {
                 f(slice, layer, method, *duration);
@@ -134,10 +301,111 @@ impl Observer {
                 layer,
                 method,
                 error,
+                ..
             } = event
             {
                 f(slice, layer, method, error);
             }
         });
     }
+
+    pub fn on_method_retry<F>(&mut self, f: F)
+    where
+        F: Fn(&str, &str, &str, usize, &str) + Send + Sync + 'static,
+    {
+        self.on_event(move |event| {
+            if let EngineEvent::MethodRetry {
+                slice,
+                layer,
+                method,
+                attempt,
+                error,
+                ..
+            } = event
+            {
+                f(slice, layer, method, *attempt, error);
+            }
+        });
+    }
+}
+
+/// Counts method and slice events, and collects method durations keyed by
+/// (layer, method). Clones share the same counters, so keep one to read after the run.
+#[derive(Clone, Default)]
+pub struct MetricsObserver {
+    inner: Arc<MetricsInner>,
+}
+
+#[derive(Default)]
+struct MetricsInner {
+    methods_started: AtomicUsize,
+    methods_completed: AtomicUsize,
+    methods_failed: AtomicUsize,
+    slices_completed: AtomicUsize,
+    method_durations: Mutex<HashMap<(String, String), Vec<Duration>>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    pub methods_started: usize,
+    pub methods_completed: usize,
+    pub methods_failed: usize,
+    pub slices_completed: usize,
+    pub method_durations: HashMap<(String, String), Vec<Duration>>,
+}
+
+impl MetricsObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A fresh `Observer` that only feeds these metrics.
+    pub fn observer(&self) -> Observer {
+        let mut observer = Observer::new();
+        self.attach(&mut observer);
+        observer
+    }
+
+    /// Adds the metrics callback to an existing observer, e.g. inside `EngineBuilder::observe`.
+    pub fn attach(&self, observer: &mut Observer) {
+        let inner = self.inner.clone();
+
+        observer.on_event(move |event| match event {
+            EngineEvent::MethodStart { .. } => {
+                inner.methods_started.fetch_add(1, Ordering::Relaxed);
+            }
+            EngineEvent::MethodComplete {
+                layer,
+                method,
+                duration,
+                ..
+            } => {
+                inner.methods_completed.fetch_add(1, Ordering::Relaxed);
+                inner
+                    .method_durations
+                    .lock()
+                    .unwrap()
+                    .entry((layer.clone(), method.clone()))
+                    .or_default()
+                    .push(*duration);
+            }
+            EngineEvent::MethodFailed { .. } => {
+                inner.methods_failed.fetch_add(1, Ordering::Relaxed);
+            }
+            EngineEvent::SliceComplete { .. } => {
+                inner.slices_completed.fetch_add(1, Ordering::Relaxed);
+            }
+            _ => {}
+        });
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            methods_started: self.inner.methods_started.load(Ordering::Relaxed),
+            methods_completed: self.inner.methods_completed.load(Ordering::Relaxed),
+            methods_failed: self.inner.methods_failed.load(Ordering::Relaxed),
+            slices_completed: self.inner.slices_completed.load(Ordering::Relaxed),
+            method_durations: self.inner.method_durations.lock().unwrap().clone(),
+        }
+    }
 }
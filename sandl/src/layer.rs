@@ -12,10 +12,15 @@ pub struct MethodConfig {
     pub default: crate::Value,
 }
 
+/// Cloning is cheap: bound methods are shared, along with any `bind_pure_cached` cache.
+#[derive(Clone)]
 pub struct Layer {
     pub name: String,
     pub methods_to_defaults: HashMap<String, crate::Value>,
     pub binds: HashMap<String, LayerMethodFn>,
+    pub context_defaults: HashMap<String, String>,
+    pub descriptions: HashMap<String, String>,
+    pub max_concurrency: Option<usize>,
 }
 
 impl Layer {
@@ -24,6 +29,9 @@ impl Layer {
             name: layer_name,
             methods_to_defaults: HashMap::new(),
             binds: HashMap::new(),
+            context_defaults: HashMap::new(),
+            descriptions: HashMap::new(),
+            max_concurrency: None,
         }
     }
 
@@ -71,6 +79,18 @@ impl Layer {
         self.binds.contains_key(method_name)
     }
 
+    /// Declared methods that were never bound, sorted by name.
+    pub fn unbound_methods(&self) -> Vec<&str> {
+        let mut unbound: Vec<&str> = self
+            .methods_to_defaults
+            .keys()
+            .filter(|method| !self.binds.contains_key(*method))
+            .map(|method| method.as_str())
+            .collect();
+        unbound.sort();
+        unbound
+    }
+
     pub fn get_name(&self) -> &str {
         &self.name
     }
@@ -85,4 +105,12 @@ impl Layer {
     pub fn get_default_args(&self, method: &str) -> Option<&crate::Value> {
         self.methods_to_defaults.get(method)
     }
+
+    pub fn get_context_default(&self, method: &str) -> Option<&str> {
+        self.context_defaults.get(method).map(|k| k.as_str())
+    }
+
+    pub fn method_description(&self, method: &str) -> Option<&str> {
+        self.descriptions.get(method).map(|d| d.as_str())
+    }
 }
@@ -1,15 +1,25 @@
 pub mod builder;
 pub mod config;
 pub mod context;
+#[cfg(feature = "csv")]
+pub mod delimited;
 pub mod engine;
 pub mod error;
 pub mod events;
 pub mod layer;
+#[cfg(feature = "serde")]
+mod pipeline;
 pub mod results;
+mod semaphore;
+mod sharded;
 pub mod slice;
+#[cfg(feature = "timestamp")]
+mod timestamp;
 pub mod tracker;
 pub mod traits;
 pub mod value;
+#[cfg(feature = "serde")]
+mod value_serde;
 
 pub use builder::*;
 pub use config::*;
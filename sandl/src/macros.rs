@@ -108,6 +108,27 @@ macro_rules! value {
     };
 }
 
+// Usage: value_dyn!({ (key_var) => some_value, "literal" => 2 });
+// Keys are any expression with `to_string()`, values follow the `value!` rules.
+#[macro_export]
+macro_rules! value_dyn {
+    ({$($key:tt => $value:tt),* $(,)?}) => {{
+        let mut map = std::collections::HashMap::new();
+        $(
+            map.insert(($key).to_string(), $crate::value!($value));
+        )*
+        $crate::Value::Object(map)
+    }};
+
+    ({$($key:tt => $value:expr),* $(,)?}) => {{
+        let mut map = std::collections::HashMap::new();
+        $(
+            map.insert(($key).to_string(), $crate::value!($value));
+        )*
+        $crate::Value::Object(map)
+    }};
+}
+
 #[macro_export]
 macro_rules! execution_error {
     ($msg:expr) => {
@@ -0,0 +1,49 @@
+use crate::*;
+
+impl Slice {
+    /// Builds a slice from `{ "layer": { "method": <args> } }`, e.g. one entry of a parsed
+    /// JSON or TOML pipeline file. Layers and binds still have to come from code.
+    pub fn from_config(name: impl Into<String>, value: &Value) -> crate::Result<Slice> {
+        let name = name.into();
+        let layers = value.as_object().ok_or_else(|| {
+            Error::ConfigError(format!(
+                "Slice '{}' config must be an object of layers",
+                name
+            ))
+        })?;
+
+        let mut slice = Slice::new(name);
+        for (layer, methods) in layers {
+            let methods = methods.as_object().ok_or_else(|| {
+                Error::ConfigError(format!(
+                    "Layer '{}' in slice '{}' must be an object of methods",
+                    layer, slice.name
+                ))
+            })?;
+
+            slice = slice.with_layer(LayerArgs {
+                layer: layer.clone(),
+                methods_args: methods.clone(),
+            });
+        }
+
+        Ok(slice)
+    }
+}
+
+impl Engine {
+    /// Builds every slice of `{ "slice": { "layer": { "method": <args> } } }`, ordered by name.
+    pub fn slices_from_config(value: &Value) -> crate::Result<Vec<Slice>> {
+        let slices = value.as_object().ok_or_else(|| {
+            Error::ConfigError("Pipeline config must be an object of slices".to_string())
+        })?;
+
+        let mut names: Vec<&String> = slices.keys().collect();
+        names.sort();
+
+        names
+            .into_iter()
+            .map(|name| Slice::from_config(name.clone(), &slices[name]))
+            .collect()
+    }
+}
@@ -1,31 +1,141 @@
-use crate::{Error, Result, Value};
-use std::{collections::HashMap, time::Duration};
+use crate::{Error, FromValue, LockStats, Result, ResultField, Value};
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+/// A `(layer, method)` pair. The engine interns the names, so keys share one allocation
+/// per name across every slice's results.
+pub type MethodKey = (Arc<str>, Arc<str>);
+
+pub fn method_key(layer: &str, method: &str) -> MethodKey {
+    (layer.into(), method.into())
+}
 
 #[derive(Debug)]
 pub struct SliceResults {
-    pub method_results: HashMap<(String, String), Result<Value>>,
+    pub method_results: HashMap<MethodKey, Result<Value>>,
     pub duration: Duration,
+    pub lock_stats: Option<LockStats>,
+    pub combined: Option<Value>,
+    pub skipped: HashSet<MethodKey>,
+    pub method_durations: HashMap<MethodKey, Duration>,
+    pub typed_results: HashMap<MethodKey, Box<dyn Any + Send + Sync>>,
 }
 
+/// Returned by methods bound with `bind_typed`. The value is converted into a `Value` as
+/// usual, and the original is also kept in `SliceResults` for `typed_result`.
+pub struct TypedResult<T>(pub T);
+
 impl SliceResults {
     pub fn new() -> Self {
         Self {
             method_results: HashMap::new(),
             duration: Duration::ZERO,
+            lock_stats: None,
+            combined: None,
+            skipped: HashSet::new(),
+            method_durations: HashMap::new(),
+            typed_results: HashMap::new(),
         }
     }
 
-    pub fn add_result(&mut self, layer: String, method: String, result: Result<Value>) {
-        self.method_results.insert((layer, method), result);
+    pub fn add_result(
+        &mut self,
+        layer: impl Into<Arc<str>>,
+        method: impl Into<Arc<str>>,
+        result: Result<Value>,
+    ) {
+        self.method_results
+            .insert((layer.into(), method.into()), result);
+    }
+
+    pub fn mark_skipped(&mut self, layer: impl Into<Arc<str>>, method: impl Into<Arc<str>>) {
+        self.skipped.insert((layer.into(), method.into()));
+    }
+
+    pub fn is_skipped(&self, layer: &str, method: &str) -> bool {
+        self.skipped.contains(&method_key(layer, method))
+    }
+
+    pub fn set_method_duration(
+        &mut self,
+        layer: impl Into<Arc<str>>,
+        method: impl Into<Arc<str>>,
+        duration: Duration,
+    ) {
+        self.method_durations
+            .insert((layer.into(), method.into()), duration);
+    }
+
+    /// How long the method took. Only recorded when the run has the observer enabled.
+    pub fn method_duration(&self, layer: &str, method: &str) -> Option<Duration> {
+        self.method_durations
+            .get(&method_key(layer, method))
+            .copied()
+    }
+
+    /// The concrete value a `bind_typed` method returned, without going through `Value`.
+    /// `None` for other methods, failed calls, or when `T` isn't the returned type.
+    pub fn typed_result<T: Any>(&self, layer: &str, method: &str) -> Option<&T> {
+        self.typed_results
+            .get(&method_key(layer, method))?
+            .downcast_ref()
     }
 
     pub fn set_duration(&mut self, duration: Duration) {
         self.duration = duration;
     }
+
+    pub fn set_lock_stats(&mut self, stats: LockStats) {
+        self.lock_stats = Some(stats);
+    }
+
+    /// Assembles the declared result fields into one object. Fields whose
+    /// method failed come out as `Null`.
+    pub fn combine(&mut self, schema: &[ResultField]) {
+        let fields = schema
+            .iter()
+            .map(|field| {
+                let value = self
+                    .method_results
+                    .get(&method_key(&field.layer, &field.method))
+                    .and_then(|result| result.as_ref().ok())
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                (field.field.clone(), value)
+            })
+            .collect();
+
+        self.combined = Some(Value::Object(fields));
+    }
+
+    pub fn take_method(&mut self, layer: &str, method: &str) -> Option<Result<Value>> {
+        self.method_results.remove(&method_key(layer, method))
+    }
 }
 
 pub type RunResults = HashMap<String, Result<SliceResults>>;
 
+/// The configuration a run actually executed with, from `Engine::run_with_meta`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunMetadata {
+    pub batch_size: Option<usize>,
+    pub chunk_size: usize,
+    pub num_threads: usize,
+    pub total_duration: Duration,
+}
+
+/// Outcome of one `(layer, method)` pair summed over every slice that called it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MethodStats {
+    pub successes: usize,
+    pub failures: usize,
+    pub values: Vec<Value>,
+}
+
 pub trait RunResultsExt {
     fn total_slices(&self) -> usize;
     fn successful_slices(&self) -> usize;
@@ -34,22 +144,61 @@ pub trait RunResultsExt {
     fn total_methods(&self) -> usize;
     fn successful_methods(&self) -> usize;
     fn failed_methods(&self) -> usize;
+    fn skipped_methods(&self) -> usize;
 
     fn is_all_success(&self) -> bool;
     fn has_failures(&self) -> bool;
     fn summary(&self) -> String;
 
     fn get_slice_errors(&self) -> Vec<(&String, &Error)>;
-    fn get_all_method_errors(&self) -> Vec<(&String, &String, &String, &Error)>;
-    fn get_execution_errors(&self) -> Vec<(&String, &String, &String, &Error)>;
+    fn get_all_method_errors(&self) -> Vec<(&String, &str, &str, &Error)>;
+    fn get_execution_errors(&self) -> Vec<(&String, &str, &str, &Error)>;
+    fn first_error(&self) -> Option<&Error>;
+    fn into_result(self) -> Result<()>;
+    fn merge(&mut self, other: RunResults);
 
     fn from_slice(&self, slice_name: &str) -> Option<&Result<SliceResults>>;
     fn slice_names(&self) -> Vec<&String>;
+    fn get_result(&self, slice: &str, layer: &str, method: &str) -> Option<&Result<Value>>;
+    fn get_typed<T: FromValue>(&self, slice: &str, layer: &str, method: &str) -> Result<T>;
+    fn by_method(&self) -> HashMap<MethodKey, MethodStats>;
 
     fn average_slice_duration(&self) -> Option<Duration>;
     fn min_slice_duration(&self) -> Option<Duration>;
     fn max_slice_duration(&self) -> Option<Duration>;
+    fn slice_duration_percentile(&self, p: f64) -> Option<Duration>;
     fn timing_summary(&self) -> String;
+    fn percentile_summary(&self) -> String;
+    fn slowest_methods(&self, n: usize) -> Vec<(MethodKey, Duration)>;
+    fn method_duration_percentile(&self, p: f64) -> Option<Duration>;
+
+    fn to_csv(&self) -> String;
+    fn to_method_csv(&self) -> String;
+
+    #[cfg(feature = "serde_json")]
+    fn to_json(&self) -> serde_json::Value;
+    #[cfg(feature = "serde_json")]
+    fn to_json_string_pretty(&self) -> String;
+}
+
+// Slices are checked in name order, and methods in (layer, method) order within a
+// slice, so the same results always report the same error
+fn first_error_location(results: &RunResults) -> Option<(&String, Option<&MethodKey>)> {
+    let mut names: Vec<&String> = results.keys().collect();
+    names.sort();
+
+    names
+        .into_iter()
+        .find_map(|slice_name| match &results[slice_name] {
+            Err(_) => Some((slice_name, None)),
+            Ok(slice_results) => slice_results
+                .method_results
+                .iter()
+                .filter(|(_, result)| result.is_err())
+                .map(|(key, _)| key)
+                .min()
+                .map(|key| (slice_name, Some(key))),
+        })
 }
 
 impl RunResultsExt for RunResults {
@@ -68,7 +217,7 @@ impl RunResultsExt for RunResults {
     fn total_methods(&self) -> usize {
         self.values()
             .filter_map(|result| result.as_ref().ok())
-            .map(|slice_results| slice_results.method_results.len())
+            .map(|slice_results| slice_results.method_results.len() + slice_results.skipped.len())
             .sum()
     }
 
@@ -98,9 +247,19 @@ impl RunResultsExt for RunResults {
             .sum()
     }
 
+    /// Methods that never produced a result: rejected by a `call_if` gate, or cut off
+    /// by a quorum. Skipped, successful and failed methods add up to `total_methods`.
+    fn skipped_methods(&self) -> usize {
+        self.values()
+            .filter_map(|result| result.as_ref().ok())
+            .map(|slice_results| slice_results.skipped.len())
+            .sum()
+    }
+
+    // Skipped methods aren't failures
     fn is_all_success(&self) -> bool {
         self.successful_slices() == self.total_slices()
-            && self.successful_methods() == self.total_methods()
+            && self.successful_methods() + self.skipped_methods() == self.total_methods()
     }
 
     fn has_failures(&self) -> bool {
@@ -112,11 +271,18 @@ impl RunResultsExt for RunResults {
         let successful_slices = self.successful_slices();
         let total_methods = self.total_methods();
         let successful_methods = self.successful_methods();
+        let skipped_methods = self.skipped_methods();
 
-        format!(
+        let summary = format!(
             "Slices: {}/{} succeeded, Methods: {}/{} succeeded",
             successful_slices, total_slices, successful_methods, total_methods
-        )
+        );
+
+        if skipped_methods > 0 {
+            format!("{}, {} skipped", summary, skipped_methods)
+        } else {
+            summary
+        }
     }
 
     fn get_slice_errors(&self) -> Vec<(&String, &Error)> {
@@ -125,14 +291,14 @@ impl RunResultsExt for RunResults {
             .collect()
     }
 
-    fn get_all_method_errors(&self) -> Vec<(&String, &String, &String, &Error)> {
+    fn get_all_method_errors(&self) -> Vec<(&String, &str, &str, &Error)> {
         let mut errors = Vec::new();
 
         for (slice_name, slice_result) in self {
             if let Ok(slice_results) = slice_result {
                 for ((layer, method), method_result) in &slice_results.method_results {
                     if let Err(e) = method_result {
-                        errors.push((slice_name, layer, method, e));
+                        errors.push((slice_name, &**layer, &**method, e));
                     }
                 }
             }
@@ -149,13 +315,112 @@ impl RunResultsExt for RunResults {
         self.keys().collect()
     }
 
-    fn get_execution_errors(&self) -> Vec<(&String, &String, &String, &Error)> {
+    fn get_result(&self, slice: &str, layer: &str, method: &str) -> Option<&Result<Value>> {
+        self.get(slice)?
+            .as_ref()
+            .ok()?
+            .method_results
+            .get(&method_key(layer, method))
+    }
+
+    // Slices that failed as a whole never produced method results, so they add nothing here
+    fn by_method(&self) -> HashMap<MethodKey, MethodStats> {
+        let mut stats: HashMap<MethodKey, MethodStats> = HashMap::new();
+
+        for slice_results in self.values().filter_map(|result| result.as_ref().ok()) {
+            for (key, result) in &slice_results.method_results {
+                let entry = stats.entry(key.clone()).or_default();
+                match result {
+                    Ok(value) => {
+                        entry.successes += 1;
+                        entry.values.push(value.clone());
+                    }
+                    Err(_) => entry.failures += 1,
+                }
+            }
+        }
+
+        stats
+    }
+
+    fn get_typed<T: FromValue>(&self, slice: &str, layer: &str, method: &str) -> Result<T> {
+        let slice_results = match self.get(slice) {
+            Some(Ok(slice_results)) => slice_results,
+            Some(Err(e)) => {
+                return Err(Error::ConfigError(format!(
+                    "Slice '{}' failed: {}",
+                    slice,
+                    e.message()
+                )));
+            }
+            None => {
+                return Err(Error::ConfigError(format!(
+                    "No results for slice '{}'",
+                    slice
+                )));
+            }
+        };
+
+        match slice_results.method_results.get(&method_key(layer, method)) {
+            Some(Ok(value)) => T::from_value(value),
+            Some(Err(e)) => Err(Error::ConfigError(format!(
+                "{}.{}.{} failed: {}",
+                slice,
+                layer,
+                method,
+                e.message()
+            ))),
+            None => Err(Error::MethodNotFound {
+                method: method.to_string(),
+                layer: layer.to_string(),
+            }),
+        }
+    }
+
+    fn get_execution_errors(&self) -> Vec<(&String, &str, &str, &Error)> {
         self.get_all_method_errors()
             .into_iter()
             .filter(|(_, _, _, error)| error.is_execution_error())
             .collect()
     }
 
+    fn first_error(&self) -> Option<&Error> {
+        let (slice_name, method) = first_error_location(self)?;
+
+        match (&self[slice_name], method) {
+            (Err(e), _) => Some(e),
+            (Ok(slice_results), Some(key)) => slice_results.method_results[key].as_ref().err(),
+            (Ok(_), None) => None,
+        }
+    }
+
+    fn into_result(mut self) -> Result<()> {
+        let Some((slice_name, method)) = first_error_location(&self)
+            .map(|(slice_name, method)| (slice_name.clone(), method.cloned()))
+        else {
+            return Ok(());
+        };
+
+        let error = match (self.remove(&slice_name), method) {
+            (Some(Err(e)), _) => e,
+            (Some(Ok(mut slice_results)), Some(key)) => {
+                match slice_results.method_results.remove(&key) {
+                    Some(Err(e)) if e.is_execution_error() => e,
+                    Some(Err(e)) => e.with_context(slice_name, &*key.0, &*key.1, Value::Null),
+                    _ => unreachable!("first_error_location points at a failed method"),
+                }
+            }
+            _ => unreachable!("first_error_location points at a failure"),
+        };
+
+        Err(error)
+    }
+
+    // Slices in `other` replace the same-named ones here, e.g. after `Engine::rerun_failed`
+    fn merge(&mut self, other: RunResults) {
+        self.extend(other);
+    }
+
     fn average_slice_duration(&self) -> Option<Duration> {
         let durations: Vec<Duration> = self
             .values()
@@ -187,6 +452,46 @@ impl RunResultsExt for RunResults {
             .max()
     }
 
+    fn slice_duration_percentile(&self, p: f64) -> Option<Duration> {
+        let durations: Vec<Duration> = self
+            .values()
+            .filter_map(|result| result.as_ref().ok())
+            .map(|slice_results| slice_results.duration)
+            .collect();
+
+        nearest_rank(durations, p)
+    }
+
+    // Each (layer, method) is ranked by its slowest call across slices
+    fn slowest_methods(&self, n: usize) -> Vec<(MethodKey, Duration)> {
+        let mut slowest: HashMap<&MethodKey, Duration> = HashMap::new();
+
+        for slice_results in self.values().filter_map(|result| result.as_ref().ok()) {
+            for (key, duration) in &slice_results.method_durations {
+                let entry = slowest.entry(key).or_default();
+                *entry = (*entry).max(*duration);
+            }
+        }
+
+        let mut ranked: Vec<(MethodKey, Duration)> = slowest
+            .into_iter()
+            .map(|(key, duration)| (key.clone(), duration))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked.truncate(n);
+        ranked
+    }
+
+    fn method_duration_percentile(&self, p: f64) -> Option<Duration> {
+        let durations: Vec<Duration> = self
+            .values()
+            .filter_map(|result| result.as_ref().ok())
+            .flat_map(|slice_results| slice_results.method_durations.values().copied())
+            .collect();
+
+        nearest_rank(durations, p)
+    }
+
     fn timing_summary(&self) -> String {
         let avg = self.average_slice_duration();
         let min = self.min_slice_duration();
@@ -199,4 +504,169 @@ impl RunResultsExt for RunResults {
             max.unwrap_or(Duration::ZERO)
         )
     }
+
+    fn percentile_summary(&self) -> String {
+        let p50 = self.slice_duration_percentile(50.0);
+        let p90 = self.slice_duration_percentile(90.0);
+        let p99 = self.slice_duration_percentile(99.0);
+
+        format!(
+            "p50: {:?}, p90: {:?}, p99: {:?}",
+            p50.unwrap_or(Duration::ZERO),
+            p90.unwrap_or(Duration::ZERO),
+            p99.unwrap_or(Duration::ZERO)
+        )
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn to_json(&self) -> serde_json::Value {
+        let slices = self
+            .iter()
+            .map(|(slice_name, slice_result)| {
+                let entry = match slice_result {
+                    Ok(slice_results) => {
+                        let methods = slice_results
+                            .method_results
+                            .iter()
+                            .map(|((layer, method), result)| {
+                                let value = match result {
+                                    Ok(value) => serde_json::Value::from(value.clone()),
+                                    Err(e) => error_to_json(e),
+                                };
+                                (format!("{}.{}", layer, method), value)
+                            })
+                            .collect::<serde_json::Map<_, _>>();
+
+                        serde_json::json!({
+                            "duration_ms": slice_results.duration.as_millis() as u64,
+                            "methods": methods,
+                        })
+                    }
+                    Err(e) => error_to_json(e),
+                };
+                (slice_name.clone(), entry)
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        serde_json::Value::Object(slices)
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn to_json_string_pretty(&self) -> String {
+        serde_json::to_string_pretty(&self.to_json()).unwrap_or_default()
+    }
+
+    // Failed slices have no timings, so their row only carries the name
+    fn to_csv(&self) -> String {
+        let mut out = String::from("slice,duration_ms,methods_total,methods_failed\n");
+
+        let mut names: Vec<&String> = self.keys().collect();
+        names.sort();
+
+        for name in names {
+            match &self[name] {
+                Ok(slice_results) => {
+                    let failed = slice_results
+                        .method_results
+                        .values()
+                        .filter(|result| result.is_err())
+                        .count();
+                    out.push_str(&format!(
+                        "{},{},{},{}\n",
+                        csv_field(name),
+                        slice_results.duration.as_millis(),
+                        slice_results.method_results.len() + slice_results.skipped.len(),
+                        failed
+                    ));
+                }
+                Err(_) => out.push_str(&format!("{},,,\n", csv_field(name))),
+            }
+        }
+
+        out
+    }
+
+    // duration_ms is only filled in for methods timed by the observer
+    fn to_method_csv(&self) -> String {
+        let mut out = String::from("slice,layer,method,status,duration_ms\n");
+
+        let mut names: Vec<&String> = self.keys().collect();
+        names.sort();
+
+        for name in names {
+            let Ok(slice_results) = &self[name] else {
+                continue;
+            };
+
+            let mut rows: Vec<(&MethodKey, &str)> = slice_results
+                .method_results
+                .iter()
+                .map(|(key, result)| (key, if result.is_ok() { "ok" } else { "failed" }))
+                .chain(slice_results.skipped.iter().map(|key| (key, "skipped")))
+                .collect();
+            rows.sort();
+
+            for (key, status) in rows {
+                let duration = slice_results
+                    .method_durations
+                    .get(key)
+                    .map(|d| d.as_millis().to_string())
+                    .unwrap_or_default();
+                out.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    csv_field(name),
+                    csv_field(&key.0),
+                    csv_field(&key.1),
+                    status,
+                    duration
+                ));
+            }
+        }
+
+        out
+    }
+}
+
+// Quotes a field when it contains a delimiter, quote or line break, doubling inner quotes
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn nearest_rank(mut durations: Vec<Duration>, p: f64) -> Option<Duration> {
+    if durations.is_empty() {
+        return None;
+    }
+
+    durations.sort();
+
+    // Nearest-rank percentile
+    let p = p.clamp(0.0, 100.0);
+    let rank = ((p / 100.0) * durations.len() as f64).ceil() as usize;
+    let index = rank.clamp(1, durations.len()) - 1;
+
+    Some(durations[index])
+}
+
+#[cfg(feature = "serde_json")]
+fn error_to_json(error: &Error) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    object.insert("error".to_string(), error.message().into());
+
+    if let Some((slice, layer, method, args)) = error.execution_context() {
+        object.insert(
+            "context".to_string(),
+            serde_json::json!({
+                "slice": slice,
+                "layer": layer,
+                "method": method,
+                "args": serde_json::Value::from(args.clone()),
+            }),
+        );
+    }
+
+    serde_json::Value::Object(object)
 }
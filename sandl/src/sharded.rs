@@ -0,0 +1,37 @@
+use crate::Value;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::RwLock;
+
+// Keys are spread over independently locked shards, so methods writing different keys
+// don't queue up behind one lock. Operations covering every key (len, keys, clear, ...)
+// lock the shards one after another, always in index order.
+const SHARDS: usize = 16;
+
+pub(crate) type Shard = RwLock<HashMap<String, Value>>;
+
+pub(crate) struct ShardedMap {
+    shards: Box<[Shard]>,
+}
+
+impl ShardedMap {
+    pub(crate) fn new() -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| RwLock::default()).collect(),
+        }
+    }
+
+    pub(crate) fn index(&self, key: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish() as usize % self.shards.len()
+    }
+
+    pub(crate) fn shard(&self, key: &str) -> &Shard {
+        &self.shards[self.index(key)]
+    }
+
+    pub(crate) fn shards(&self) -> &[Shard] {
+        &self.shards
+    }
+}
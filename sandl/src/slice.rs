@@ -1,22 +1,42 @@
 use std::collections::HashMap;
 
-use crate::Value;
+use crate::{Context, DependencyPredicate, Value};
 
 pub struct LayerArgs {
     pub layer: String,
     pub methods_args: HashMap<String, Value>,
 }
 
+#[derive(Clone)]
+pub struct ResultField {
+    pub field: String,
+    pub layer: String,
+    pub method: String,
+}
+
+#[derive(Clone)]
 pub struct Slice {
     pub name: String,
+    pub id: Option<String>,
     pub methods_per_layer: HashMap<String, HashMap<String, Value>>,
+    pub result_schema: Vec<ResultField>,
+    pub quorum: Option<usize>,
+    pub priority: i32,
+    pub conditions: HashMap<(String, String), DependencyPredicate>,
+    pub seed: HashMap<String, Value>,
 }
 
 impl Slice {
     pub fn new(name: String) -> Self {
         Self {
             name,
+            id: None,
             methods_per_layer: HashMap::new(),
+            result_schema: Vec::new(),
+            quorum: None,
+            priority: 0,
+            conditions: HashMap::new(),
+            seed: HashMap::new(),
         }
     }
 
@@ -26,6 +46,40 @@ impl Slice {
         self
     }
 
+    pub fn with_result_field(mut self, field: ResultField) -> Self {
+        self.result_schema.push(field);
+        self
+    }
+
+    /// See `SliceBuilder::quorum`.
+    pub fn with_quorum(mut self, quorum: usize) -> Self {
+        self.quorum = Some(quorum.max(1));
+        self
+    }
+
+    /// See `LayerMethodsBuilder::call_if`.
+    pub fn with_condition(
+        mut self,
+        layer: impl Into<String>,
+        method: impl Into<String>,
+        predicate: DependencyPredicate,
+    ) -> Self {
+        self.conditions
+            .insert((layer.into(), method.into()), predicate);
+        self
+    }
+
+    /// False when the method's `call_if` predicate rejects the context. Unconditional
+    /// methods always run.
+    pub fn should_run(&self, layer: &str, method: &str, ctx: &Context) -> bool {
+        if self.conditions.is_empty() {
+            return true;
+        }
+        self.conditions
+            .get(&(layer.to_string(), method.to_string()))
+            .is_none_or(|predicate| predicate(ctx))
+    }
+
     pub fn has_layer(&self, layer: &str) -> bool {
         self.methods_per_layer.contains_key(layer)
     }
@@ -60,4 +114,9 @@ impl Slice {
     pub fn get_name(&self) -> &str {
         &self.name
     }
+
+    /// The id carried by this slice's events; the name unless one was set.
+    pub fn get_id(&self) -> &str {
+        self.id.as_deref().unwrap_or(&self.name)
+    }
 }
@@ -1,9 +1,26 @@
+use std::io::Write;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use crate::{RunResults, RunResultsExt};
 
+/// Shared destination for progress lines and the run summary.
+#[derive(Clone)]
+pub struct ProgressWriter(Arc<Mutex<Box<dyn Write + Send>>>);
+
+impl ProgressWriter {
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self(Arc::new(Mutex::new(writer)))
+    }
+}
+
+impl std::fmt::Debug for ProgressWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressWriter")
+    }
+}
+
 pub struct ProgressTracker {
     total: usize,
     completed: Arc<AtomicUsize>,
@@ -11,6 +28,9 @@ pub struct ProgressTracker {
     start_time: Instant,
     last_print: Arc<Mutex<Instant>>,
     run_time: Duration,
+    verbose_summary: bool,
+    writer: Option<ProgressWriter>,
+    interval: Duration,
 }
 
 impl ProgressTracker {
@@ -22,6 +42,41 @@ impl ProgressTracker {
             start_time: Instant::now(),
             last_print: Arc::new(Mutex::new(Instant::now())),
             run_time: Duration::ZERO,
+            verbose_summary: false,
+            writer: None,
+            interval: Duration::from_millis(50),
+        }
+    }
+
+    pub fn verbose_summary(mut self, verbose: bool) -> Self {
+        self.verbose_summary = verbose;
+        self
+    }
+
+    /// Sends output here instead of stdout.
+    pub fn writer(mut self, writer: Option<ProgressWriter>) -> Self {
+        self.writer = writer;
+        self
+    }
+
+    /// Minimum time between progress lines (a line is also printed every 1%).
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    fn write_out(&self, text: &str) {
+        match &self.writer {
+            Some(ProgressWriter(writer)) => {
+                let mut writer = writer.lock().unwrap();
+                let _ = writer.write_all(text.as_bytes());
+                let _ = writer.flush();
+            }
+            None => {
+                let mut stdout = std::io::stdout().lock();
+                let _ = stdout.write_all(text.as_bytes());
+                let _ = stdout.flush();
+            }
         }
     }
 
@@ -52,10 +107,10 @@ impl ProgressTracker {
 
         let should_print = {
             let mut last = self.last_print.lock().unwrap();
-            let elapsed_since_print = last.elapsed().as_millis();
+            let elapsed_since_print = last.elapsed();
 
-            // Print if 50ms has passed OR we've completed another 1%
-            if elapsed_since_print >= 50
+            // Print if the interval has passed OR we've completed another 1%
+            if elapsed_since_print >= self.interval
                 || (total_done > 0 && total_done % (self.total / 100).max(1) == 0)
             {
                 *last = Instant::now();
@@ -78,34 +133,71 @@ impl ProgressTracker {
         let elapsed = self.start_time.elapsed();
 
         // Clear line and print progress
-        print!("\r\x1B[K"); // Clear current line
-        print!(
-            "Progress: [{}/{}] {}% | ✓ {} ✗ {} | {:?}",
+        let mut line = format!(
+            "\r\x1B[KProgress: [{}/{}] {}% | ✓ {} ✗ {} | {:?}",
             total_done, self.total, percent, completed, failed, elapsed
         );
 
-        use std::io::Write;
-        let _ = std::io::stdout().flush();
+        if let Some(eta) = estimate_eta(total_done, self.total, elapsed) {
+            line.push_str(&format!(" | ETA: {}", format_hms(eta)));
+        }
 
         if total_done == self.total {
-            println!(); // New line when complete
+            line.push('\n'); // New line when complete
         }
+
+        self.write_out(&line);
     }
 
     pub fn print_header(&self) {
-        println!("Starting execution of {} slices...", self.total);
+        self.write_out(&format!("Starting execution of {} slices...\n", self.total));
     }
 
     pub fn print_summary(&self, results: &RunResults) {
+        self.write_out(&self.summary_text(results));
+    }
+
+    pub fn summary_text(&self, results: &RunResults) -> String {
         let elapsed = self.start_time.elapsed();
-        println!("{}", results.summary());
-        println!("Total: {:?} | {}", elapsed, results.timing_summary());
+        let mut text = format!("{}\n", results.summary());
+        text.push_str(&format!(
+            "Total: {:?} | {}\n",
+            elapsed,
+            results.timing_summary()
+        ));
+
+        if self.verbose_summary {
+            text.push_str(&format!("Percentiles: {}\n", results.percentile_summary()));
+        }
 
         if results.has_failures() {
-            println!("\nErrors occurred:");
+            text.push_str("\nErrors occurred:\n");
             for (slice, layer, method, error) in results.get_all_method_errors() {
-                println!("  ✗ {}.{}.{}: {}", slice, layer, method, error.message());
+                text.push_str(&format!(
+                    "  ✗ {}.{}.{}: {}\n",
+                    slice,
+                    layer,
+                    method,
+                    error.message()
+                ));
             }
         }
+
+        text
+    }
+}
+
+/// Extrapolates the time left from the average pace so far. `None` until something is done.
+pub fn estimate_eta(done: usize, total: usize, elapsed: Duration) -> Option<Duration> {
+    if done == 0 {
+        return None;
     }
+
+    let remaining = total.saturating_sub(done);
+    Some(elapsed.mul_f64(remaining as f64 / done as f64))
+}
+
+fn format_hms(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
 }
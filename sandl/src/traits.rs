@@ -195,6 +195,42 @@ impl<T: ToValue> ToValue for Option<T> {
     }
 }
 
+// Tuples map to arrays of the same length
+macro_rules! impl_tuple {
+    ($len:expr; $($name:ident $index:tt),+) => {
+        impl<$($name: FromValue),+> FromValue for ($($name,)+) {
+            fn from_value(value: &Value) -> Result<Self> {
+                let arr = value
+                    .as_array()
+                    .ok_or_else(|| Error::ConfigError("Expected array".into()))?;
+
+                if arr.len() != $len {
+                    return Err(Error::ConfigError(format!(
+                        "Expected array of length {}, got {}",
+                        $len,
+                        arr.len()
+                    )));
+                }
+
+                Ok(($($name::from_value(&arr[$index])?,)+))
+            }
+        }
+
+        impl<$($name: ToValue),+> ToValue for ($($name,)+) {
+            fn to_value(&self) -> Value {
+                Value::Array(vec![$(self.$index.to_value()),+])
+            }
+        }
+    };
+}
+
+impl_tuple!(1; A 0);
+impl_tuple!(2; A 0, B 1);
+impl_tuple!(3; A 0, B 1, C 2);
+impl_tuple!(4; A 0, B 1, C 2, D 3);
+impl_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+
 impl<T: FromValue> FromValue for HashMap<String, T> {
     fn from_value(value: &Value) -> Result<Self> {
         let obj = value
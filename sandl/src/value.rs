@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
@@ -8,9 +10,15 @@ pub enum Value {
     String(String),
     Array(Vec<Value>),
     Object(HashMap<String, Value>),
+    /// Milliseconds since the Unix epoch; serialized as an ISO-8601 string.
+    #[cfg(feature = "timestamp")]
+    Timestamp(i64),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// Numbers compare by value, regardless of variant: `Int(5)`, `Size(5)` and `Float(5.0)`
+/// are all equal. Floats only equal integers they represent exactly, so there's no
+/// tolerance for rounding error (`0.1 + 0.2` is not `0.3`), and NaN equals nothing.
+#[derive(Debug, Clone)]
 pub enum Number {
     UnsignedInt(u64),
     Int(i64),
@@ -18,6 +26,58 @@ pub enum Number {
     Float(f64),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberKind {
+    UnsignedInt,
+    Int,
+    Size,
+    Float,
+}
+
+impl Number {
+    pub fn kind(&self) -> NumberKind {
+        match self {
+            Number::UnsignedInt(_) => NumberKind::UnsignedInt,
+            Number::Int(_) => NumberKind::Int,
+            Number::Size(_) => NumberKind::Size,
+            Number::Float(_) => NumberKind::Float,
+        }
+    }
+
+    // Returns None when the conversion would lose data: a fractional or
+    // non-finite float to an integer kind, an out-of-range integer, or an
+    // integer too large to be represented exactly as a float.
+    pub fn to_kind(&self, target: NumberKind) -> Option<Number> {
+        if self.kind() == target {
+            return Some(self.clone());
+        }
+
+        let integer: Option<i128> = match self {
+            Number::UnsignedInt(i) => Some(*i as i128),
+            Number::Int(i) => Some(*i as i128),
+            Number::Size(i) => Some(*i as i128),
+            Number::Float(f) => {
+                if f.is_finite() && f.fract() == 0.0 && f.abs() < 2f64.powi(127) {
+                    Some(*f as i128)
+                } else {
+                    None
+                }
+            }
+        };
+
+        match target {
+            NumberKind::Float => {
+                let i = integer?;
+                let f = i as f64;
+                (f as i128 == i).then_some(Number::Float(f))
+            }
+            NumberKind::Int => i64::try_from(integer?).ok().map(Number::Int),
+            NumberKind::UnsignedInt => u64::try_from(integer?).ok().map(Number::UnsignedInt),
+            NumberKind::Size => usize::try_from(integer?).ok().map(Number::Size),
+        }
+    }
+}
+
 impl Value {
     pub fn null() -> Self {
         Value::Null
@@ -57,6 +117,9 @@ impl Value {
     pub fn as_i64(&self) -> Option<i64> {
         match self {
             Value::Number(Number::Int(i)) => Some(*i),
+            // Unsigned values above i64::MAX have no i64 form
+            Value::Number(Number::Size(i)) => i64::try_from(*i).ok(),
+            Value::Number(Number::UnsignedInt(i)) => i64::try_from(*i).ok(),
             Value::Number(Number::Float(f)) => Some(*f as i64),
             _ => None,
         }
@@ -66,10 +129,32 @@ impl Value {
         match self {
             Value::Number(Number::Float(f)) => Some(*f),
             Value::Number(Number::Int(i)) => Some(*i as f64),
+            Value::Number(Number::Size(i)) => Some(*i as f64),
+            Value::Number(Number::UnsignedInt(i)) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    /// Any numeric variant as `f64`, for arithmetic that doesn't care which one it got.
+    /// Same as `as_f64`.
+    pub fn as_number(&self) -> Option<f64> {
+        self.as_f64()
+    }
+
+    /// Integer variants, losslessly. Floats are rejected even when they're whole.
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            Value::Number(Number::Int(i)) => Some(*i as i128),
+            Value::Number(Number::UnsignedInt(i)) => Some(*i as i128),
+            Value::Number(Number::Size(i)) => Some(*i as i128),
             _ => None,
         }
     }
 
+    pub fn is_number(&self) -> bool {
+        matches!(self, Value::Number(_))
+    }
+
     pub fn as_str(&self) -> Option<&str> {
         match self {
             Value::String(s) => Some(s),
@@ -113,9 +198,257 @@ impl Value {
         self.as_object_mut()?.get_mut(key)
     }
 
+    /// The map entry for `key`, or `None` when this isn't an object.
+    pub fn entry(&mut self, key: &str) -> Option<Entry<'_, String, Value>> {
+        Some(self.as_object_mut()?.entry(key.to_string()))
+    }
+
     pub fn get_index(&self, index: usize) -> Option<&Value> {
         self.as_array()?.get(index)
     }
+
+    /// Walks nested objects and arrays along `path`, e.g. `"stats/0/min"` or `"stats.0.min"`.
+    /// Numeric segments index into arrays; any missing key or type mismatch yields `None`.
+    pub fn get_path(&self, path: &str) -> Option<&Value> {
+        path_segments(path).try_fold(self, |value, segment| match value {
+            Value::Object(map) => map.get(segment),
+            Value::Array(arr) => arr.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+    }
+
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut Value> {
+        path_segments(path).try_fold(self, |value, segment| match value {
+            Value::Object(map) => map.get_mut(segment),
+            Value::Array(arr) => arr.get_mut(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+    }
+
+    /// JSON with object keys sorted and `indent` spaces per level, so equal values
+    /// always print identically. Meant for golden files and snapshot tests.
+    pub fn to_pretty_sorted(&self, indent: usize) -> String {
+        let mut out = String::new();
+        write_json(self, &mut out, Some(indent), 0);
+        out
+    }
+
+    /// Merges `other` into `self`, recursing where both sides hold an object under
+    /// the same key. Anything else (arrays, scalars, mismatched types) is replaced.
+    pub fn deep_merge(&mut self, other: &Value) {
+        match (self, other) {
+            (Value::Object(base), Value::Object(overrides)) => {
+                for (key, value) in overrides {
+                    match base.get_mut(key) {
+                        Some(existing) => existing.deep_merge(value),
+                        None => {
+                            base.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+            }
+            (this, other) => *this = other.clone(),
+        }
+    }
+
+    /// Recursively converts every number to `target`. Lossy conversions are
+    /// rejected: on error `self` is left untouched.
+    pub fn coerce_numbers_to(&mut self, target: NumberKind) -> crate::Result<()> {
+        *self = self.coerced_numbers(target)?;
+        Ok(())
+    }
+
+    fn coerced_numbers(&self, target: NumberKind) -> crate::Result<Value> {
+        match self {
+            Value::Number(n) => n.to_kind(target).map(Value::Number).ok_or_else(|| {
+                crate::Error::ConfigError(format!(
+                    "Cannot convert {:?} to {:?} losslessly",
+                    n, target
+                ))
+            }),
+            Value::Array(arr) => arr
+                .iter()
+                .map(|v| v.coerced_numbers(target))
+                .collect::<crate::Result<Vec<Value>>>()
+                .map(Value::Array),
+            Value::Object(obj) => obj
+                .iter()
+                .map(|(k, v)| v.coerced_numbers(target).map(|v| (k.clone(), v)))
+                .collect::<crate::Result<HashMap<String, Value>>>()
+                .map(Value::Object),
+            other => Ok(other.clone()),
+        }
+    }
+}
+
+// `None` writes compact JSON with no whitespace at all
+fn write_json(value: &Value, out: &mut String, indent: Option<usize>, depth: usize) {
+    let newline = |out: &mut String, depth: usize| {
+        if let Some(indent) = indent {
+            out.push('\n');
+            out.push_str(&" ".repeat(indent * depth));
+        }
+    };
+
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_json_number(n, out),
+        Value::String(s) => write_json_string(s, out),
+        #[cfg(feature = "timestamp")]
+        Value::Timestamp(millis) => {
+            write_json_string(&crate::timestamp::format_iso8601(*millis), out)
+        }
+        Value::Array(arr) if arr.is_empty() => out.push_str("[]"),
+        Value::Array(arr) => {
+            out.push('[');
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, depth + 1);
+                write_json(item, out, indent, depth + 1);
+            }
+            newline(out, depth);
+            out.push(']');
+        }
+        Value::Object(map) if map.is_empty() => out.push_str("{}"),
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, depth + 1);
+                write_json_string(key, out);
+                out.push_str(if indent.is_some() { ": " } else { ":" });
+                write_json(&map[key], out, indent, depth + 1);
+            }
+            newline(out, depth);
+            out.push('}');
+        }
+    }
+}
+
+impl Number {
+    // The exact integer this number holds, if any. Every integer variant fits in i128,
+    // and so does any whole float in the combined i64/u64 range.
+    fn as_exact_int(&self) -> Option<i128> {
+        match self {
+            Number::UnsignedInt(i) => Some(*i as i128),
+            Number::Int(i) => Some(*i as i128),
+            Number::Size(i) => Some(*i as i128),
+            Number::Float(f) => {
+                let in_range = *f >= i64::MIN as f64 && *f < u64::MAX as f64;
+                (f.fract() == 0.0 && in_range).then_some(*f as i128)
+            }
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Number::Float(a), Number::Float(b)) => a == b,
+            _ => match (self.as_exact_int(), other.as_exact_int()) {
+                (Some(a), Some(b)) => a == b,
+                _ => false,
+            },
+        }
+    }
+}
+
+// Numbers that hold an exact integer hash by it, whatever their variant, so equal
+// numbers hash alike (-0.0 included). Other floats hash by bit pattern. NaN is never
+// equal to itself, so a NaN-containing value can be inserted into a set but never
+// looked up again.
+impl Eq for Number {}
+
+impl Hash for Number {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match (self.as_exact_int(), self) {
+            (Some(i), _) => i.hash(state),
+            (None, Number::Float(f)) => f.to_bits().hash(state),
+            // Integer variants are always exact
+            (None, _) => {}
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::Null => {}
+            Value::Bool(b) => b.hash(state),
+            Value::Number(n) => n.hash(state),
+            Value::String(s) => s.hash(state),
+            #[cfg(feature = "timestamp")]
+            Value::Timestamp(millis) => millis.hash(state),
+            Value::Array(arr) => arr.hash(state),
+            Value::Object(map) => {
+                // XOR of per-entry hashes doesn't depend on the map's iteration order
+                let combined = map.iter().fold(0u64, |acc, (key, value)| {
+                    let mut entry = DefaultHasher::new();
+                    key.hash(&mut entry);
+                    value.hash(&mut entry);
+                    acc ^ entry.finish()
+                });
+                map.len().hash(state);
+                combined.hash(state);
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        write_json(self, &mut out, None, 0);
+        f.write_str(&out)
+    }
+}
+
+fn write_json_number(n: &Number, out: &mut String) {
+    use std::fmt::Write;
+
+    let _ = match n {
+        Number::UnsignedInt(i) => write!(out, "{}", i),
+        Number::Int(i) => write!(out, "{}", i),
+        Number::Size(i) => write!(out, "{}", i),
+        // Debug keeps the trailing ".0" on whole floats; JSON has no NaN or infinity
+        Number::Float(f) if f.is_finite() => write!(out, "{:?}", f),
+        Number::Float(_) => write!(out, "null"),
+    };
+}
+
+fn write_json_string(s: &str, out: &mut String) {
+    use std::fmt::Write;
+
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn path_segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split(['/', '.']).filter(|segment| !segment.is_empty())
 }
 
 impl From<()> for Value {
@@ -230,6 +563,10 @@ impl From<Value> for serde_json::Value {
                 .map(serde_json::Value::Number)
                 .unwrap_or(serde_json::Value::Null),
             Value::String(s) => serde_json::Value::String(s),
+            #[cfg(feature = "timestamp")]
+            Value::Timestamp(millis) => {
+                serde_json::Value::String(crate::timestamp::format_iso8601(millis))
+            }
             Value::Array(arr) => {
                 serde_json::Value::Array(arr.into_iter().map(serde_json::Value::from).collect())
             }
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::{self, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Number, Value};
+
+// Human-readable formats (JSON, TOML, YAML) get the natural shape, like the serde_json
+// conversion. Compact formats such as bincode can't deserialize untyped data, so there
+// the value is written as a tagged enum, which also keeps the exact number variant.
+const VARIANTS: &[&str] = &[
+    "Null",
+    "Bool",
+    "UnsignedInt",
+    "Int",
+    "Size",
+    "Float",
+    "String",
+    "Array",
+    "Object",
+    "Timestamp",
+];
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serialize_plain(self, serializer)
+        } else {
+            serialize_tagged(self, serializer)
+        }
+    }
+}
+
+fn serialize_plain<S: Serializer>(value: &Value, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Value::Null => serializer.serialize_unit(),
+        Value::Bool(b) => serializer.serialize_bool(*b),
+        Value::Number(Number::UnsignedInt(n)) => serializer.serialize_u64(*n),
+        Value::Number(Number::Int(n)) => serializer.serialize_i64(*n),
+        Value::Number(Number::Size(n)) => serializer.serialize_u64(*n as u64),
+        Value::Number(Number::Float(f)) => serializer.serialize_f64(*f),
+        Value::String(s) => serializer.serialize_str(s),
+        #[cfg(feature = "timestamp")]
+        Value::Timestamp(millis) => {
+            serializer.serialize_str(&crate::timestamp::format_iso8601(*millis))
+        }
+        Value::Array(items) => serializer.collect_seq(items),
+        Value::Object(map) => {
+            // Sorted, so the same value always serializes the same way
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+
+            let mut out = serializer.serialize_map(Some(entries.len()))?;
+            for (key, value) in entries {
+                out.serialize_entry(key, value)?;
+            }
+            out.end()
+        }
+    }
+}
+
+fn serialize_tagged<S: Serializer>(value: &Value, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Value::Null => serializer.serialize_unit_variant("Value", 0, VARIANTS[0]),
+        Value::Bool(b) => serializer.serialize_newtype_variant("Value", 1, VARIANTS[1], b),
+        Value::Number(Number::UnsignedInt(n)) => {
+            serializer.serialize_newtype_variant("Value", 2, VARIANTS[2], n)
+        }
+        Value::Number(Number::Int(n)) => {
+            serializer.serialize_newtype_variant("Value", 3, VARIANTS[3], n)
+        }
+        Value::Number(Number::Size(n)) => {
+            serializer.serialize_newtype_variant("Value", 4, VARIANTS[4], &(*n as u64))
+        }
+        Value::Number(Number::Float(f)) => {
+            serializer.serialize_newtype_variant("Value", 5, VARIANTS[5], f)
+        }
+        Value::String(s) => serializer.serialize_newtype_variant("Value", 6, VARIANTS[6], s),
+        Value::Array(items) => serializer.serialize_newtype_variant("Value", 7, VARIANTS[7], items),
+        Value::Object(map) => serializer.serialize_newtype_variant("Value", 8, VARIANTS[8], map),
+        #[cfg(feature = "timestamp")]
+        Value::Timestamp(millis) => {
+            serializer.serialize_newtype_variant("Value", 9, VARIANTS[9], millis)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(PlainVisitor)
+        } else {
+            deserializer.deserialize_enum("Value", VARIANTS, TaggedVisitor)
+        }
+    }
+}
+
+struct PlainVisitor;
+
+impl<'de> Visitor<'de> for PlainVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any sandl value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_bool<E: de::Error>(self, b: bool) -> Result<Value, E> {
+        Ok(Value::Bool(b))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Value, E> {
+        Ok(Value::Number(Number::Int(n)))
+    }
+
+    // Integers become `Int` whenever they fit, like the serde_json conversion
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Value, E> {
+        Ok(match i64::try_from(n) {
+            Ok(n) => Value::Number(Number::Int(n)),
+            Err(_) => Value::Number(Number::UnsignedInt(n)),
+        })
+    }
+
+    fn visit_f64<E: de::Error>(self, f: f64) -> Result<Value, E> {
+        Ok(Value::Number(Number::Float(f)))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Value, E> {
+        Ok(Value::String(s.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, s: String) -> Result<Value, E> {
+        Ok(Value::String(s))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut entries = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((key, value)) = map.next_entry()? {
+            entries.insert(key, value);
+        }
+        Ok(Value::Object(entries))
+    }
+}
+
+struct TaggedVisitor;
+
+impl<'de> Visitor<'de> for TaggedVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a tagged sandl value")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Value, A::Error> {
+        let (index, variant): (u32, _) = data.variant()?;
+
+        Ok(match index {
+            0 => {
+                variant.unit_variant()?;
+                Value::Null
+            }
+            1 => Value::Bool(variant.newtype_variant()?),
+            2 => Value::Number(Number::UnsignedInt(variant.newtype_variant()?)),
+            3 => Value::Number(Number::Int(variant.newtype_variant()?)),
+            4 => {
+                let n: u64 = variant.newtype_variant()?;
+                let n = usize::try_from(n).map_err(de::Error::custom)?;
+                Value::Number(Number::Size(n))
+            }
+            5 => Value::Number(Number::Float(variant.newtype_variant()?)),
+            6 => Value::String(variant.newtype_variant()?),
+            7 => Value::Array(variant.newtype_variant()?),
+            8 => Value::Object(variant.newtype_variant()?),
+            #[cfg(feature = "timestamp")]
+            9 => Value::Timestamp(variant.newtype_variant()?),
+            other => {
+                return Err(de::Error::invalid_value(
+                    de::Unexpected::Unsigned(other as u64),
+                    &"a Value variant index",
+                ));
+            }
+        })
+    }
+}
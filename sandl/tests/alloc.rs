@@ -0,0 +1,113 @@
+use sandl::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Records the largest allocation, so a test can tell whether a big value was copied, and
+// counts allocations of one watched size, so a test can tell how often a string was copied
+struct LargestAllocation;
+
+static LARGEST: AtomicUsize = AtomicUsize::new(0);
+static WATCHED_SIZE: AtomicUsize = AtomicUsize::new(usize::MAX);
+static WATCHED_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for LargestAllocation {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LARGEST.fetch_max(layout.size(), Ordering::SeqCst);
+        if layout.size() == WATCHED_SIZE.load(Ordering::SeqCst) {
+            WATCHED_COUNT.fetch_add(1, Ordering::SeqCst);
+        }
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: LargestAllocation = LargestAllocation;
+
+#[derive(Args)]
+struct SumArgs {
+    items: Vec<i64>,
+}
+
+#[test]
+fn slice_args_are_not_cloned_per_call() {
+    const ITEMS: usize = 10_000;
+
+    let layer = Layer::builder("math")
+        .method("sum")
+        .args::<SumArgs>()
+        .bind_pure(|args| Ok(value!(args.items.iter().sum::<i64>())))
+        .build();
+
+    let items = Value::Array((0..ITEMS as i64).map(Value::from).collect());
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("s")
+                .layer("math", |m| m.call("sum", value!({ "items": (items) })))
+                .build(),
+        )
+        .config(EngineConfig::new().sequential())
+        .build()
+        .unwrap();
+
+    LARGEST.store(0, Ordering::SeqCst);
+    let results = engine.run(RunFlags::SILENT_NO_OBSERVER);
+    let largest = LARGEST.load(Ordering::SeqCst);
+
+    let expected = (0..ITEMS as i64).sum::<i64>();
+    let sum = results.get_typed::<i64>("s", "math", "sum").unwrap();
+    assert_eq!(sum, expected);
+
+    // Converting to SumArgs allocates a Vec<i64>; a copy of the args would allocate
+    // an array of Values, which is several times bigger
+    assert!(
+        largest < ITEMS * std::mem::size_of::<Value>(),
+        "{}",
+        largest
+    );
+}
+
+#[test]
+fn method_names_are_not_copied_per_call() {
+    const SLICES: usize = 1_000;
+    // An odd length nothing else in a run is likely to allocate
+    const NAME_LEN: usize = 4_099;
+
+    let layer_name = "l".repeat(NAME_LEN);
+    let method_name = "m".repeat(NAME_LEN);
+
+    let layer = Layer::builder(layer_name.as_str())
+        .method(method_name.as_str())
+        .args::<Value>()
+        .bind_pure(|_args| Ok(Value::Null))
+        .build();
+
+    let mut builder = Engine::builder()
+        .add_layer(layer)
+        .config(EngineConfig::new().sequential());
+    for i in 0..SLICES {
+        builder = builder.add_slice(
+            Slice::builder(format!("s{}", i))
+                .layer(layer_name.as_str(), |m| {
+                    m.call_default(method_name.as_str())
+                })
+                .build(),
+        );
+    }
+    let engine = builder.build().unwrap();
+
+    WATCHED_COUNT.store(0, Ordering::SeqCst);
+    WATCHED_SIZE.store(NAME_LEN, Ordering::SeqCst);
+    let results = engine.run(RunFlags::SILENT_NO_OBSERVER);
+    WATCHED_SIZE.store(usize::MAX, Ordering::SeqCst);
+    let copies = WATCHED_COUNT.load(Ordering::SeqCst);
+
+    assert_eq!(results.successful_slices(), SLICES);
+    // Result keys share the engine's interned names; owned keys would copy both names
+    // for every slice
+    assert!(copies < SLICES, "{}", copies);
+}
@@ -28,7 +28,7 @@ fn args_default_values() {
     let slice_results = results.get("test").unwrap().as_ref().unwrap();
     let result = slice_results
         .method_results
-        .get(&("layer".to_string(), "work".to_string()))
+        .get(&method_key("layer", "work"))
         .unwrap()
         .as_ref()
         .unwrap();
@@ -62,7 +62,7 @@ fn args_explicit_override() {
     let slice_results = results.get("test").unwrap().as_ref().unwrap();
     let result = slice_results
         .method_results
-        .get(&("layer".to_string(), "multiply".to_string()))
+        .get(&method_key("layer", "multiply"))
         .unwrap()
         .as_ref()
         .unwrap();
@@ -97,7 +97,7 @@ fn args_partial_override() {
     let slice_results = results.get("test").unwrap().as_ref().unwrap();
     let result = slice_results
         .method_results
-        .get(&("layer".to_string(), "compute".to_string()))
+        .get(&method_key("layer", "compute"))
         .unwrap()
         .as_ref()
         .unwrap();
@@ -168,7 +168,7 @@ fn args_no_defaults() {
     let slice_results = results.get("test").unwrap().as_ref().unwrap();
     let result = slice_results
         .method_results
-        .get(&("layer".to_string(), "work".to_string()))
+        .get(&method_key("layer", "work"))
         .unwrap()
         .as_ref()
         .unwrap();
@@ -207,7 +207,7 @@ fn args_different_per_slice() {
     let s1_result = results.get("s1").unwrap().as_ref().unwrap();
     let s1_value = s1_result
         .method_results
-        .get(&("layer".to_string(), "process".to_string()))
+        .get(&method_key("layer", "process"))
         .unwrap()
         .as_ref()
         .unwrap();
@@ -216,7 +216,7 @@ fn args_different_per_slice() {
     let s2_result = results.get("s2").unwrap().as_ref().unwrap();
     let s2_value = s2_result
         .method_results
-        .get(&("layer".to_string(), "process".to_string()))
+        .get(&method_key("layer", "process"))
         .unwrap()
         .as_ref()
         .unwrap();
@@ -249,10 +249,233 @@ fn args_array_type() {
     let slice_results = results.get("test").unwrap().as_ref().unwrap();
     let result = slice_results
         .method_results
-        .get(&("layer".to_string(), "sum".to_string()))
+        .get(&method_key("layer", "sum"))
         .unwrap()
         .as_ref()
         .unwrap();
 
     assert_eq!(result.get("sum").unwrap().as_i64().unwrap(), 15);
 }
+
+#[test]
+fn args_from_context_key() {
+    let configure = quick_layer!("configure", "setup", Value, |args, ctx| {
+        if let Some(factor) = args.get("factor") {
+            ctx.set("scale_args", value!({ "factor": factor.clone() }));
+        }
+        Ok(value!({}))
+    });
+
+    let scale = Layer::builder("scale")
+        .method("apply")
+        .args_with_default(value!({ "factor": 1, "base": 10 }))
+        .args_from_context("scale_args")
+        .bind(|args, _ctx| {
+            let factor = args.get("factor").unwrap().as_i64().unwrap();
+            let base = args.get("base").unwrap().as_i64().unwrap();
+            Ok(value!({ "result": factor * base }))
+        })
+        .build();
+
+    let from_context = Slice::builder("from_context")
+        .layer("configure", |m| m.call("setup", value!({ "factor": 3 })))
+        .layer("scale", |m| m.call_default("apply"))
+        .build();
+    let fallback = Slice::builder("fallback")
+        .layer("configure", |m| m.call_default("setup"))
+        .layer("scale", |m| m.call_default("apply"))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(configure)
+        .add_layer(scale)
+        .dependency("scale", "configure")
+        .add_slice(from_context)
+        .add_slice(fallback)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let result_of = |slice: &str| {
+        results.get(slice).unwrap().as_ref().unwrap().method_results[&method_key("scale", "apply")]
+            .as_ref()
+            .unwrap()
+            .get("result")
+            .unwrap()
+            .as_i64()
+            .unwrap()
+    };
+
+    assert_eq!(result_of("from_context"), 30);
+    assert_eq!(result_of("fallback"), 10);
+}
+
+#[test]
+fn args_deep_merge_nested_defaults() {
+    let layer = Layer::builder("layer")
+        .method("connect")
+        .args_with_default(value!({ "db": { "host": "x", "port": 5432 } }))
+        .bind(|args, _ctx| Ok(args.clone()))
+        .build();
+
+    let slice = Slice::builder("test")
+        .layer("layer", |m| {
+            m.call("connect", value!({ "db": { "port": 5433 } }))
+        })
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .config(EngineConfig::new().deep_merge_args(true))
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let result = results
+        .get("test")
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .method_results[&method_key("layer", "connect")]
+        .as_ref()
+        .unwrap();
+
+    assert_eq!(result, &value!({ "db": { "host": "x", "port": 5433 } }));
+}
+
+#[test]
+fn args_injected_from_env() {
+    // Unique names so parallel tests can't observe each other's variables
+    unsafe {
+        std::env::set_var("SANDL_TEST_API_URL", "https://api.example.com");
+        std::env::remove_var("SANDL_TEST_MISSING_REGION");
+    }
+
+    let layer = Layer::builder("layer")
+        .method("call")
+        .args_with_default(value!({ "timeout": 30 }))
+        .bind(|args, _ctx| Ok(args.clone()))
+        .build();
+
+    let injected = Slice::builder("injected")
+        .layer("layer", |m| m.call_default("call"))
+        .build();
+    let overridden = Slice::builder("overridden")
+        .layer("layer", |m| {
+            m.call("call", value!({ "api_url": "http://local" }))
+        })
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(injected)
+        .add_slice(overridden)
+        .env_args(&[
+            ("api_url", "SANDL_TEST_API_URL"),
+            ("region", "SANDL_TEST_MISSING_REGION"),
+        ])
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let args_of = |slice: &str| {
+        results.get(slice).unwrap().as_ref().unwrap().method_results[&method_key("layer", "call")]
+            .as_ref()
+            .unwrap()
+            .clone()
+    };
+
+    assert_eq!(
+        args_of("injected"),
+        value!({ "timeout": 30, "api_url": "https://api.example.com" })
+    );
+    assert_eq!(
+        args_of("overridden"),
+        value!({ "timeout": 30, "api_url": "http://local" })
+    );
+}
+
+#[test]
+fn env_args_fill_methods_without_defaults() {
+    unsafe {
+        std::env::set_var("SANDL_TEST_NULL_TOKEN", "secret");
+    }
+
+    let layer = Layer::builder("layer")
+        .method("call")
+        .args::<Value>()
+        .bind(|args, _ctx| Ok(args.clone()))
+        .build();
+
+    let slice = Slice::builder("test")
+        .layer("layer", |m| m.call_default("call"))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .env_args(&[("token", "SANDL_TEST_NULL_TOKEN")])
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let result = results
+        .get("test")
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .method_results[&method_key("layer", "call")]
+        .as_ref()
+        .unwrap()
+        .clone();
+
+    assert_eq!(result, value!({ "token": "secret" }));
+}
+
+#[test]
+fn env_args_override_defaults_but_not_slice_args() {
+    unsafe {
+        std::env::set_var("SANDL_TEST_PRECEDENCE_HOST", "from-env");
+    }
+
+    let layer = Layer::builder("layer")
+        .method("call")
+        .args_with_default(value!({ "host": "from-default", "port": 80 }))
+        .bind(|args, _ctx| Ok(args.clone()))
+        .build();
+
+    let defaulted = Slice::builder("defaulted")
+        .layer("layer", |m| m.call_default("call"))
+        .build();
+    let explicit = Slice::builder("explicit")
+        .layer("layer", |m| {
+            m.call("call", value!({ "host": "from-slice" }))
+        })
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(defaulted)
+        .add_slice(explicit)
+        .env_args(&[("host", "SANDL_TEST_PRECEDENCE_HOST")])
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let args_of = |slice: &str| {
+        results.get(slice).unwrap().as_ref().unwrap().method_results[&method_key("layer", "call")]
+            .as_ref()
+            .unwrap()
+            .clone()
+    };
+
+    assert_eq!(
+        args_of("defaulted"),
+        value!({ "host": "from-env", "port": 80 })
+    );
+    assert_eq!(
+        args_of("explicit"),
+        value!({ "host": "from-slice", "port": 80 })
+    );
+}
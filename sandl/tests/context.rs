@@ -4,6 +4,7 @@ use std::sync::{
 };
 
 use sandl::*;
+use std::time::Duration;
 
 #[test]
 fn context_basic_operations() {
@@ -359,3 +360,486 @@ fn context_shared_across_layers() {
 
     engine.run(RunFlags::SILENT);
 }
+
+#[test]
+fn context_emit_streams_to_sink() {
+    let layer = quick_layer!("layer", "generate", Value, |_args, ctx| {
+        for i in 0..1000i64 {
+            ctx.emit(Value::from(i));
+        }
+        Ok(Value::Null)
+    });
+
+    let slice = Slice::builder("test")
+        .layer("layer", |m| m.call_default("generate"))
+        .build();
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let sum = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+    let s = sum.clone();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .emit_sink(move |slice, value| {
+            assert_eq!(slice, "test");
+            c.fetch_add(1, Ordering::SeqCst);
+            s.fetch_add(value.as_i64().unwrap() as usize, Ordering::SeqCst);
+        })
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let slice_results = results.get("test").unwrap().as_ref().unwrap();
+    let result = slice_results
+        .method_results
+        .get(&method_key("layer", "generate"))
+        .unwrap()
+        .as_ref()
+        .unwrap();
+
+    assert!(result.is_null());
+    assert_eq!(count.load(Ordering::SeqCst), 1000);
+    assert_eq!(sum.load(Ordering::SeqCst), (0..1000).sum::<usize>());
+}
+
+#[test]
+fn context_lock_contention_is_recorded() {
+    let methods: Vec<String> = (0..8).map(|i| format!("m{}", i)).collect();
+
+    let mut builder = Layer::builder("hot");
+    for method in &methods {
+        builder = builder
+            .method(method.clone())
+            .args::<Value>()
+            .bind(|_args, ctx| {
+                for n in 0..2000 {
+                    // Large values make the write lock stay held while the old one drops
+                    ctx.set("hot", Value::Array(vec![Value::from(n); 256]));
+                    ctx.get("hot");
+                }
+                Ok(Value::Null)
+            });
+    }
+
+    let slice = Slice::builder("s1").layer("hot", |m| {
+        methods
+            .iter()
+            .fold(m, |m, method| m.call_default(method.clone()))
+    });
+
+    let engine = Engine::builder()
+        .add_layer(builder.build())
+        .add_slice(slice.build())
+        .config(
+            EngineConfig::new()
+                .num_threads(4)
+                .track_lock_contention(true),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let stats = results
+        .get("s1")
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .lock_stats
+        .unwrap();
+
+    assert_eq!(stats.acquisitions, 8 * 2000 * 2);
+    assert!(stats.contended > 0, "{:?}", stats);
+    assert!(stats.wait > Duration::ZERO);
+}
+
+#[test]
+fn context_lock_stats_off_by_default() {
+    let ctx = Context::new();
+    ctx.set("key", Value::from(1));
+    assert!(ctx.lock_stats().is_none());
+}
+
+#[test]
+fn context_child_reads_parent() {
+    let parent = Context::new();
+    parent.set("config", Value::from("prod"));
+
+    let child = parent.child();
+    assert_eq!(child.get("config").unwrap().as_str(), Some("prod"));
+    assert!(child.contains("config"));
+
+    child.set("config", Value::from("test"));
+    assert_eq!(child.get("config").unwrap().as_str(), Some("test"));
+    assert_eq!(parent.get("config").unwrap().as_str(), Some("prod"));
+}
+
+#[test]
+fn context_child_writes_stay_local_until_merged() {
+    let parent = Context::new();
+    let child = parent.child();
+
+    child.set("scratch", Value::from(1));
+    assert!(!parent.contains("scratch"));
+
+    child.merge_into_parent();
+    assert_eq!(parent.get_as::<i64>("scratch").unwrap(), 1);
+}
+
+#[test]
+fn context_child_keys_union_both_levels() {
+    let parent = Context::new();
+    parent.set("a", Value::from(1));
+    parent.set("shared", Value::from(1));
+
+    let child = parent.child();
+    child.set("b", Value::from(2));
+    child.set("shared", Value::from(2));
+
+    let mut keys = child.keys();
+    keys.sort();
+    assert_eq!(keys, vec!["a", "b", "shared"]);
+    assert_eq!(child.len(), 3);
+    assert_eq!(parent.len(), 2);
+}
+
+#[test]
+fn context_update_is_atomic_across_methods() {
+    let methods: Vec<String> = (0..8).map(|i| format!("m{}", i)).collect();
+
+    let mut builder = Layer::builder("counter");
+    for method in &methods {
+        builder = builder
+            .method(method.clone())
+            .args::<Value>()
+            .bind(|_args, ctx| {
+                for _ in 0..500 {
+                    ctx.update("count", |current| {
+                        let n = current.and_then(|v| v.as_i64()).unwrap_or(0);
+                        Value::from(n + 1)
+                    });
+                }
+                Ok(Value::Null)
+            });
+    }
+
+    let check = quick_layer!("check", "read", Value, |_args, ctx| {
+        Ok(ctx.get("count").unwrap())
+    });
+
+    let slice = Slice::builder("s1")
+        .layer("counter", |m| {
+            methods
+                .iter()
+                .fold(m, |m, method| m.call_default(method.clone()))
+        })
+        .layer("check", |m| m.call_default("read"))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(builder.build())
+        .add_layer(check)
+        .dependency("check", "counter")
+        .add_slice(slice)
+        .config(EngineConfig::new().num_threads(4))
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let s1 = results.get("s1").unwrap().as_ref().unwrap();
+
+    assert_eq!(
+        s1.method_results[&method_key("check", "read")]
+            .as_ref()
+            .unwrap()
+            .as_i64(),
+        Some(8 * 500)
+    );
+}
+
+#[test]
+fn context_get_or_insert_with() {
+    let ctx = Context::new();
+
+    let first = ctx.get_or_insert_with("id", || Value::from(1));
+    let second = ctx.get_or_insert_with("id", || Value::from(2));
+
+    assert_eq!(first.as_i64(), Some(1));
+    assert_eq!(second.as_i64(), Some(1));
+    assert_eq!(ctx.get_as::<i64>("id").unwrap(), 1);
+}
+
+#[test]
+fn shared_context_seeds_every_slice() {
+    let reads = Arc::new(AtomicUsize::new(0));
+
+    let r = reads.clone();
+    let layer = quick_layer!("layer", "work", Value, move |args, ctx| {
+        assert_eq!(ctx.get_as::<String>("env").unwrap(), "prod");
+        assert!(ctx.get("mine").is_none());
+
+        let id = args.get("id").unwrap().as_i64().unwrap();
+        ctx.set("mine", Value::from(id));
+        ctx.set("env", Value::from("dev"));
+
+        r.fetch_add(1, Ordering::SeqCst);
+        Ok(value!({}))
+    });
+
+    let shared = Context::new();
+    shared.set("env", Value::from("prod"));
+
+    let mut builder = Engine::builder()
+        .config(EngineConfig::new().sequential())
+        .with_shared_context(shared.clone())
+        .add_layer(layer);
+    for id in 1..=3 {
+        builder = builder.add_slice(
+            Slice::builder(format!("s{}", id))
+                .layer("layer", |m| m.call("work", value!({ "id": id })))
+                .build(),
+        );
+    }
+    let engine = builder.build().unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(results.failed_methods(), 0);
+    assert_eq!(reads.load(Ordering::SeqCst), 3);
+    assert_eq!(shared.get_as::<String>("env").unwrap(), "prod");
+}
+
+#[test]
+fn context_on_change_sees_every_write() {
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let s = seen.clone();
+
+    let layer = quick_layer!("layer", "work", Value, move |_args, ctx| {
+        let s = s.clone();
+        ctx.on_change(Arc::new(move |key, value| {
+            s.lock().unwrap().push((key.to_string(), value.clone()));
+        }));
+
+        ctx.set("rows", value!(10));
+        ctx.set_from("status", "done".to_string());
+        Ok(value!(null))
+    });
+
+    let slice = Slice::builder("s")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .build()
+        .unwrap();
+
+    engine.run(RunFlags::SILENT);
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![
+            ("rows".to_string(), value!(10)),
+            ("status".to_string(), value!("done")),
+        ]
+    );
+}
+
+#[test]
+fn context_set_many_and_get_many() {
+    let ctx = Context::new();
+    ctx.set_many((1..=5).map(|i| (format!("k{}", i), Value::from(i as i64 * 10))));
+
+    let values = ctx
+        .get_many::<i64>(&["k1", "k2", "k3", "k4", "k5"])
+        .unwrap();
+    assert_eq!(values, vec![10, 20, 30, 40, 50]);
+
+    let err = ctx.get_many::<i64>(&["k1", "missing"]).unwrap_err();
+    assert!(err.to_string().contains("Key 'missing' not found"));
+}
+
+#[test]
+fn context_restore_rolls_back_to_snapshot() {
+    let ctx = Context::new();
+    ctx.set("a", value!(1));
+    ctx.set("b", value!(2));
+
+    let snapshot = ctx.snapshot();
+
+    ctx.set("a", value!(100));
+    ctx.remove("b");
+    ctx.set("c", value!(3));
+
+    ctx.restore(snapshot.clone());
+
+    assert_eq!(ctx.snapshot(), snapshot);
+    assert_eq!(ctx.len(), 2);
+    assert_eq!(ctx.get("a"), Some(value!(1)));
+    assert_eq!(ctx.get("b"), Some(value!(2)));
+    assert!(!ctx.contains("c"));
+}
+
+#[test]
+fn context_writes_to_other_keys_proceed_while_one_is_locked() {
+    let ctx = Context::new();
+    let mut writers = Vec::new();
+
+    // `update` holds the lock for "held" while its closure runs. Each key gets its own
+    // writer, so the ones that share a shard with "held" can't hold up the rest; with
+    // 32 keys, some land on other shards whatever the hash layout
+    ctx.update("held", |_| {
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        writers = (0..32)
+            .map(|i| {
+                let other = ctx.clone();
+                let done_tx = done_tx.clone();
+                std::thread::spawn(move || {
+                    other.set(format!("other_{}", i), Value::from(i));
+                    let _ = done_tx.send(i);
+                })
+            })
+            .collect();
+
+        // With a single lock, no write could finish until the closure returns
+        let first = done_rx.recv_timeout(Duration::from_secs(5));
+        assert!(first.is_ok(), "writers to other keys were blocked");
+        Value::from(true)
+    });
+
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    assert_eq!(ctx.len(), 33);
+}
+
+#[test]
+fn update_closure_using_the_context_panics_for_any_key() {
+    // Whether a key shares a shard with "held" doesn't matter: every access is rejected
+    for i in 0..32 {
+        let ctx = Context::new();
+        let key = format!("other_{}", i);
+        let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            ctx.update("held", |_| ctx.get(&key).unwrap_or(Value::Null))
+        }));
+        assert!(
+            outcome.is_err(),
+            "reading {} inside update didn't panic",
+            key
+        );
+    }
+
+    let ctx = Context::new();
+    let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        ctx.get_or_insert_with("held", || Value::from(ctx.len()))
+    }));
+    assert!(outcome.is_err());
+}
+
+#[test]
+fn update_leaves_the_key_unchanged_when_the_closure_panics() {
+    let ctx = Context::new();
+    ctx.set("counter", Value::from(1));
+
+    let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        ctx.update("counter", |_| panic!("closure failed"))
+    }));
+    assert!(outcome.is_err());
+
+    assert_eq!(ctx.get_as::<i64>("counter").unwrap(), 1);
+    ctx.update("counter", |current| {
+        Value::from(current.and_then(|v| v.as_i64()).unwrap_or(0) + 1)
+    });
+    assert_eq!(ctx.get_as::<i64>("counter").unwrap(), 2);
+}
+
+#[test]
+fn context_parallel_writers_to_distinct_keys() {
+    let ctx = Context::new();
+
+    std::thread::scope(|scope| {
+        for t in 0..8 {
+            let ctx = &ctx;
+            scope.spawn(move || {
+                for i in 0..500 {
+                    ctx.set(format!("{}_{}", t, i), Value::from(i));
+                }
+            });
+        }
+    });
+
+    assert_eq!(ctx.len(), 8 * 500);
+    assert_eq!(ctx.get_as::<i64>("7_499").unwrap(), 499);
+}
+
+#[test]
+fn get_as_or_falls_back_on_missing_or_mismatched_keys() {
+    let ctx = Context::new();
+    ctx.set_from("count", 42i64);
+    ctx.set_from("name", "Alice");
+
+    assert_eq!(ctx.get_as_or("count", 0i64), 42);
+    assert_eq!(ctx.get_as_or("missing", 7i64), 7);
+    assert_eq!(ctx.get_as_or("name", 7i64), 7);
+}
+
+#[test]
+fn get_as_opt_separates_missing_from_wrong_type() {
+    let ctx = Context::new();
+    ctx.set_from("count", 42i64);
+    ctx.set_from("name", "Alice");
+
+    assert_eq!(ctx.get_as_opt::<i64>("count").unwrap(), Some(42));
+    assert_eq!(ctx.get_as_opt::<i64>("missing").unwrap(), None);
+    assert!(ctx.get_as_opt::<i64>("name").is_err());
+}
+
+#[test]
+fn bind_ctx_out_writes_outputs_to_the_context() {
+    let stats = Layer::builder("stats")
+        .method("range")
+        .args::<Vec<i64>>()
+        .bind_ctx_out(|items, _ctx| {
+            let mut outputs = std::collections::HashMap::new();
+            outputs.insert("min".to_string(), value!(*items.iter().min().unwrap()));
+            outputs.insert("max".to_string(), value!(*items.iter().max().unwrap()));
+            Ok(outputs)
+        })
+        .build();
+
+    let report = quick_layer!("report", "spread", Value, |_args, ctx| {
+        let min: i64 = ctx.get_as("stats.range.min")?;
+        let max: i64 = ctx.get_as("stats.range.max")?;
+        Ok(value!(max - min))
+    });
+
+    let engine = Engine::builder()
+        .add_layer(stats)
+        .add_layer(report)
+        .dependency("report", "stats")
+        .add_slice(
+            Slice::builder("s")
+                .layer("stats", |m| m.call("range", vec![4i64, 9, 1]))
+                .layer("report", |m| m.call_default("spread"))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(
+        results.get_typed::<i64>("s", "report", "spread").unwrap(),
+        8
+    );
+    assert_eq!(
+        results
+            .get_result("s", "stats", "range")
+            .unwrap()
+            .as_ref()
+            .unwrap(),
+        &value!({ "min": 1, "max": 9 })
+    );
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "csv")]
+
+use sandl::*;
+use std::path::PathBuf;
+
+fn write_temp(file_name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("sandl-{}-{}", std::process::id(), file_name));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn slices_from_csv_rows() {
+    let path = write_temp(
+        "params.csv",
+        "name,rate,label\nfast,10,\"a, b\"\nslow,0.5,c\n",
+    );
+
+    let slices = Engine::slices_from_delimited(&path, "sim", "step", b',').unwrap();
+    let inferred = Engine::slices_from_delimited_inferred(&path, "sim", "step", b',').unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let names: Vec<&str> = slices.iter().map(|s| s.get_name()).collect();
+    assert_eq!(names, vec!["fast", "slow"]);
+
+    let args = slices[0].get_method_arg("sim", "step").unwrap();
+    assert_eq!(args, &value!({ "rate": "10", "label": "a, b" }));
+
+    let args = inferred[1].get_method_arg("sim", "step").unwrap();
+    assert_eq!(args, &value!({ "rate": 0.5, "label": "c" }));
+    let args = inferred[0].get_method_arg("sim", "step").unwrap();
+    assert_eq!(args.get("rate").unwrap().as_i64(), Some(10));
+}
+
+#[test]
+fn slices_from_tsv_run_through_engine() {
+    let path = write_temp("params.tsv", "name\tx\none\t1\ntwo\t2\n");
+    let slices = Engine::slices_from_delimited_inferred(&path, "math", "double", b'\t').unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let layer = quick_layer!("math", "double", Value, |args, _ctx| {
+        Ok(Value::from(args.get("x").unwrap().as_i64().unwrap() * 2))
+    });
+
+    let mut slices = slices;
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slices(&mut slices)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let two = results.get("two").unwrap().as_ref().unwrap();
+    assert_eq!(
+        two.method_results[&method_key("math", "double")]
+            .as_ref()
+            .unwrap(),
+        &Value::from(4i64)
+    );
+}
+
+#[test]
+fn slices_from_delimited_requires_name_column() {
+    let path = write_temp("no_name.csv", "x,y\n1,2\n");
+    let result = Engine::slices_from_delimited(&path, "math", "double", b',');
+    std::fs::remove_file(&path).unwrap();
+
+    match result {
+        Err(Error::ConfigError(msg)) => assert!(msg.contains("'name'"), "{}", msg),
+        other => panic!("Expected a config error, got {:?}", other.map(|s| s.len())),
+    }
+}
@@ -0,0 +1,81 @@
+use sandl::*;
+
+#[derive(Args)]
+struct RetryArgs {
+    url: String,
+    #[arg(default = 100)]
+    retries: i64,
+    #[arg(default = "GET")]
+    verb: String,
+}
+
+#[test]
+fn derive_default_fills_missing_field() {
+    let args = RetryArgs::from_value(&value!({ "url": "http://x" })).unwrap();
+    assert_eq!(args.url, "http://x");
+    assert_eq!(args.retries, 100);
+    assert_eq!(args.verb, "GET");
+
+    let args = RetryArgs::from_value(&value!({ "url": "http://x", "retries": 3 })).unwrap();
+    assert_eq!(args.retries, 3);
+}
+
+#[test]
+fn derive_missing_field_without_default_errors() {
+    let err = RetryArgs::from_value(&value!({ "retries": 3 }))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("'url'"));
+}
+
+#[derive(Args)]
+struct RangeArgs {
+    #[arg(rename = "start-byte")]
+    start: i64,
+    #[arg(rename = "end-byte", default = 0)]
+    end: i64,
+}
+
+#[test]
+fn derive_rename_maps_field_to_key() {
+    let args = RangeArgs::from_value(&value!({ "start-byte": 4, "end-byte": 9 })).unwrap();
+    assert_eq!(args.start, 4);
+    assert_eq!(args.end, 9);
+
+    let value = args.to_value();
+    assert_eq!(value.get("start-byte"), Some(&value!(4)));
+    assert_eq!(value.get("end-byte"), Some(&value!(9)));
+    assert_eq!(value.get("start"), None);
+
+    let err = RangeArgs::from_value(&value!({ "start": 4 }))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("'start-byte'"));
+}
+
+#[derive(Args)]
+struct LabelArgs {
+    id: i64,
+    label: Option<String>,
+}
+
+#[test]
+fn derive_option_fields_are_optional() {
+    let present = LabelArgs::from_value(&value!({ "id": 1, "label": "north" })).unwrap();
+    assert_eq!(present.label.as_deref(), Some("north"));
+
+    let null = LabelArgs::from_value(&value!({ "id": 1, "label": (Value::Null) })).unwrap();
+    assert_eq!(null.label, None);
+
+    let absent = LabelArgs::from_value(&value!({ "id": 1 })).unwrap();
+    assert_eq!(absent.label, None);
+
+    assert_eq!(absent.to_value(), value!({ "id": 1 }));
+    assert_eq!(present.to_value(), value!({ "id": 1, "label": "north" }));
+}
+
+#[test]
+fn derive_ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -144,7 +144,7 @@ fn errors_returned_in_results() {
 
     let method_result = slice_result
         .method_results
-        .get(&("l1".to_string(), "m1".to_string()))
+        .get(&method_key("l1", "m1"))
         .unwrap();
 
     assert!(method_result.is_err());
@@ -179,7 +179,7 @@ fn method_failure_does_not_stop_other_methods() {
     assert!(
         slice_result
             .method_results
-            .get(&("l1".to_string(), "m1".to_string()))
+            .get(&method_key("l1", "m1"))
             .unwrap()
             .is_err()
     );
@@ -187,7 +187,7 @@ fn method_failure_does_not_stop_other_methods() {
     assert!(
         slice_result
             .method_results
-            .get(&("l1".to_string(), "m2".to_string()))
+            .get(&method_key("l1", "m2"))
             .unwrap()
             .is_ok()
     );
@@ -256,7 +256,7 @@ fn results_keyed_by_layer_and_method() {
     let results = engine.run(RunFlags::SILENT);
     let slice_result = results.get("s1").unwrap().as_ref().unwrap();
 
-    let key = ("l1".to_string(), "m1".to_string());
+    let key = method_key("l1", "m1");
     assert!(slice_result.method_results.contains_key(&key));
 }
 
@@ -339,7 +339,7 @@ fn args_and_context_together() {
 
     let layer1_result = slice_results
         .method_results
-        .get(&("layer1".to_string(), "process".to_string()))
+        .get(&method_key("layer1", "process"))
         .unwrap()
         .as_ref()
         .unwrap();
@@ -347,7 +347,7 @@ fn args_and_context_together() {
 
     let layer2_result = slice_results
         .method_results
-        .get(&("layer2".to_string(), "add".to_string()))
+        .get(&method_key("layer2", "add"))
         .unwrap()
         .as_ref()
         .unwrap();
@@ -406,7 +406,7 @@ fn pipeline_with_args_and_context() {
     let slice_results = results.get("etl").unwrap().as_ref().unwrap();
     let result = slice_results
         .method_results
-        .get(&("load".to_string(), "save".to_string()))
+        .get(&method_key("load", "save"))
         .unwrap()
         .as_ref()
         .unwrap();
@@ -576,3 +576,2245 @@ fn init_layer_nonexistent() {
         .build()
         .unwrap();
 }
+
+#[test]
+fn fail_fast_cancels_remaining_slices() {
+    let layer = quick_layer!("layer", "work", Value, |args, _ctx| {
+        if args.get("fail").and_then(|v| v.as_bool()).unwrap_or(false) {
+            Err(execution_error!("first slice failed"))
+        } else {
+            Ok(value!({}))
+        }
+    });
+
+    let s1 = Slice::builder("s1")
+        .layer("layer", |m| m.call("work", value!({ "fail": true })))
+        .build();
+    let s2 = Slice::builder("s2")
+        .layer("layer", |m| m.call("work", value!({ "fail": false })))
+        .build();
+    let s3 = Slice::builder("s3")
+        .layer("layer", |m| m.call("work", value!({ "fail": false })))
+        .build();
+
+    // A single thread dispatches slices in registration order
+    let engine = add_slices!(Engine::builder().add_layer(layer), s1, s2, s3)
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT.fail_fast());
+
+    assert_eq!(results.len(), 3);
+    assert!(results.get("s1").unwrap().is_ok());
+    assert!(matches!(results.get("s2").unwrap(), Err(Error::Cancelled)));
+    assert!(matches!(results.get("s3").unwrap(), Err(Error::Cancelled)));
+}
+
+#[test]
+fn cancellation_token_stops_remaining_slices() {
+    let (started_tx, started_rx) = std::sync::mpsc::channel();
+    let started_tx = Mutex::new(started_tx);
+
+    let layer = quick_layer!("layer", "work", Value, move |args, _ctx| {
+        if args.get("first").and_then(|v| v.as_bool()).unwrap_or(false) {
+            started_tx.lock().unwrap().send(()).unwrap();
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        Ok(value!({}))
+    });
+
+    let s1 = Slice::builder("s1")
+        .layer("layer", |m| m.call("work", value!({ "first": true })))
+        .build();
+    let s2 = Slice::builder("s2")
+        .layer("layer", |m| m.call("work", value!({ "first": false })))
+        .build();
+    let s3 = Slice::builder("s3")
+        .layer("layer", |m| m.call("work", value!({ "first": false })))
+        .build();
+
+    let engine = add_slices!(Engine::builder().add_layer(layer), s1, s2, s3)
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    let token = CancellationToken::new();
+    let canceller = {
+        let token = token.clone();
+        std::thread::spawn(move || {
+            started_rx.recv().unwrap();
+            token.cancel();
+        })
+    };
+
+    let results = engine.run_with_cancel(token.clone(), RunFlags::SILENT);
+    canceller.join().unwrap();
+
+    assert!(token.is_cancelled());
+    assert_eq!(results.len(), 3);
+    assert!(results.get("s1").unwrap().is_ok());
+    assert!(matches!(results.get("s2").unwrap(), Err(Error::Cancelled)));
+    assert!(matches!(results.get("s3").unwrap(), Err(Error::Cancelled)));
+}
+
+#[test]
+fn bind_pure_cached_reuses_results_for_equal_args() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+
+    let layer = Layer::builder("layer")
+        .method("square")
+        .args::<Value>()
+        .bind_pure_cached(move |args| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let n = args.get("n").and_then(|v| v.as_i64()).unwrap_or(0);
+            Ok(value!(n * n))
+        })
+        .build();
+
+    let s1 = Slice::builder("s1")
+        .layer("layer", |m| m.call("square", value!({ "n": 7 })))
+        .build();
+    let s2 = Slice::builder("s2")
+        .layer("layer", |m| m.call("square", value!({ "n": 7 })))
+        .build();
+    let s3 = Slice::builder("s3")
+        .layer("layer", |m| m.call("square", value!({ "n": 3 })))
+        .build();
+
+    let engine = add_slices!(Engine::builder().add_layer(layer), s1, s2, s3)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    for (slice, expected) in [("s1", 49), ("s2", 49), ("s3", 9)] {
+        let value = results.get_result(slice, "layer", "square").unwrap();
+        assert_eq!(value.as_ref().unwrap(), &value!(expected));
+    }
+}
+
+#[test]
+fn cache_key_fields_ignore_other_args() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+
+    let layer = Layer::builder("layer")
+        .method("square")
+        .args::<Value>()
+        .cache_key_fields(&["input"])
+        .bind_pure_cached(move |args| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let n = args.get("input").and_then(|v| v.as_i64()).unwrap_or(0);
+            Ok(value!(n * n))
+        })
+        .build();
+
+    let s1 = Slice::builder("s1")
+        .layer("layer", |m| {
+            m.call("square", value!({ "input": 5, "id": "req-1" }))
+        })
+        .build();
+    let s2 = Slice::builder("s2")
+        .layer("layer", |m| {
+            m.call("square", value!({ "input": 5, "id": "req-2" }))
+        })
+        .build();
+
+    let engine = add_slices!(Engine::builder().add_layer(layer), s1, s2)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    for slice in ["s1", "s2"] {
+        let value = results.get_result(slice, "layer", "square").unwrap();
+        assert_eq!(value.as_ref().unwrap(), &value!(25));
+    }
+}
+
+#[test]
+fn bind_pure_cached_retries_errors_and_skips_nan_keys() {
+    let finite_calls = Arc::new(AtomicUsize::new(0));
+    let nan_calls = Arc::new(AtomicUsize::new(0));
+    let (finite, nan) = (finite_calls.clone(), nan_calls.clone());
+
+    let layer = Layer::builder("layer")
+        .method("half")
+        .args::<Value>()
+        .bind_pure_cached(move |args| {
+            let n = args.get("n").and_then(|v| v.as_f64()).unwrap_or(0.0);
+            if n.is_nan() {
+                nan.fetch_add(1, Ordering::SeqCst);
+            } else if finite.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(execution_error!("first call fails"));
+            }
+            Ok(value!(n / 2.0))
+        })
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("finite")
+                .layer("layer", |m| m.call("half", value!({ "n": 4 })))
+                .build(),
+        )
+        .add_slice(
+            Slice::builder("nan")
+                .layer("layer", |m| m.call("half", value!({ "n": (f64::NAN) })))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let first = engine.run(RunFlags::SILENT);
+    assert!(
+        first
+            .get_result("finite", "layer", "half")
+            .unwrap()
+            .is_err()
+    );
+    for _ in 0..3 {
+        let results = engine.run(RunFlags::SILENT);
+        let value = results.get_result("finite", "layer", "half").unwrap();
+        assert_eq!(value.as_ref().unwrap(), &value!(2.0));
+    }
+
+    // The failure wasn't cached, the success after it was; NaN args never match
+    assert_eq!(finite_calls.load(Ordering::SeqCst), 2);
+    assert_eq!(nan_calls.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn bind_pure_cached_recomputes_after_a_panic() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+
+    let layer = Layer::builder("layer")
+        .method("square")
+        .args::<Value>()
+        .bind_pure_cached(move |args| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                panic!("first call blows up");
+            }
+            let n = args.get("n").and_then(|v| v.as_i64()).unwrap_or(0);
+            Ok(value!(n * n))
+        })
+        .build();
+
+    let slice = Slice::builder("s")
+        .layer("layer", |m| m.call("square", value!({ "n": 4 })))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .config(EngineConfig::new().sequential().catch_panics(true))
+        .build()
+        .unwrap();
+
+    let first = engine.run(RunFlags::SILENT);
+    assert!(first.get_result("s", "layer", "square").unwrap().is_err());
+
+    let second = engine.run(RunFlags::SILENT);
+    let value = second.get_result("s", "layer", "square").unwrap();
+    assert_eq!(value.as_ref().unwrap(), &value!(16));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn without_fail_fast_all_slices_run() {
+    let layer = quick_layer!("layer", "work", Value, |_args, _ctx| {
+        Err(execution_error!("always fails"))
+    });
+
+    let s1 = Slice::builder("s1")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+    let s2 = Slice::builder("s2")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+
+    let engine = add_slices!(Engine::builder().add_layer(layer), s1, s2)
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert!(results.values().all(|r| r.is_ok()));
+    assert_eq!(results.failed_methods(), 2);
+}
+
+#[test]
+fn run_init_only_skips_work_layers() {
+    let init_calls = Arc::new(AtomicUsize::new(0));
+    let work_calls = Arc::new(AtomicUsize::new(0));
+
+    let i = init_calls.clone();
+    let init = quick_layer!("init", "setup", Value, move |_args, _ctx| {
+        i.fetch_add(1, Ordering::SeqCst);
+        Ok(value!({ "ready": true }))
+    });
+
+    let w = work_calls.clone();
+    let work = quick_layer!("work", "process", Value, move |_args, _ctx| {
+        w.fetch_add(1, Ordering::SeqCst);
+        Ok(value!({}))
+    });
+
+    let s1 = Slice::builder("s1")
+        .layer("init", |m| m.call_default("setup"))
+        .layer("work", |m| m.call_default("process"))
+        .build();
+    let s2 = Slice::builder("s2")
+        .layer("init", |m| m.call_default("setup"))
+        .layer("work", |m| m.call_default("process"))
+        .build();
+
+    let engine = add_slices!(Engine::builder().add_layer(init).add_layer(work), s1, s2)
+        .init_layer("init")
+        .build()
+        .unwrap();
+
+    let results = engine.run_init_only(RunFlags::SILENT).unwrap();
+
+    assert_eq!(init_calls.load(Ordering::SeqCst), 2);
+    assert_eq!(work_calls.load(Ordering::SeqCst), 0);
+
+    let s1_results = results.get("s1").unwrap().as_ref().unwrap();
+    assert_eq!(s1_results.method_results.len(), 1);
+    assert!(
+        s1_results
+            .method_results
+            .contains_key(&method_key("init", "setup"))
+    );
+}
+
+#[test]
+fn run_init_only_requires_init_layer() {
+    let work = quick_layer!("work", "process", Value, |_args, _ctx| { Ok(value!({})) });
+
+    let engine = Engine::builder().add_layer(work).build().unwrap();
+
+    let result = engine.run_init_only(RunFlags::SILENT);
+    assert!(matches!(result, Err(Error::ConfigError(_))));
+}
+
+#[test]
+fn topological_sort_is_deterministic() {
+    let names = ["echo", "alpha", "delta", "charlie", "bravo"];
+
+    let mut builder = Engine::builder();
+    for name in names {
+        builder = builder.add_layer(quick_layer!(name, "work", Value, |_args, _ctx| {
+            Ok(value!({}))
+        }));
+    }
+    let engine = builder.build().unwrap();
+
+    let expected = vec!["alpha", "bravo", "charlie", "delta", "echo"];
+    for _ in 0..10 {
+        assert_eq!(engine.topological_sort().unwrap(), expected);
+    }
+}
+
+#[test]
+fn topological_sort_respects_dependencies_with_ties() {
+    let names = ["a", "b", "c", "d"];
+
+    let mut builder = Engine::builder();
+    for name in names {
+        builder = builder.add_layer(quick_layer!(name, "work", Value, |_args, _ctx| {
+            Ok(value!({}))
+        }));
+    }
+    let engine = builder.dependency("a", "d").build().unwrap();
+
+    assert_eq!(engine.topological_sort().unwrap(), vec!["b", "c", "d", "a"]);
+}
+
+#[test]
+fn retry_policy_recovers_transient_failures() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let a = attempts.clone();
+    let layer = quick_layer!("layer", "flaky", Value, move |_args, _ctx| {
+        if a.fetch_add(1, Ordering::SeqCst) < 2 {
+            Err(execution_error!("transient"))
+        } else {
+            Ok(value!({ "ok": true }))
+        }
+    });
+
+    let slice = Slice::builder("s1")
+        .layer("layer", |m| m.call_default("flaky"))
+        .build();
+
+    let retries = Arc::new(Mutex::new(Vec::new()));
+    let r = retries.clone();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .config(EngineConfig::new().retry(RetryPolicy::new(3, Duration::from_millis(1))))
+        .observe(move |observer| {
+            observer.on_method_retry(move |_, _, _, attempt, _| {
+                r.lock().unwrap().push(attempt);
+            });
+        })
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let slice_results = results.get("s1").unwrap().as_ref().unwrap();
+    let result = slice_results
+        .method_results
+        .get(&method_key("layer", "flaky"))
+        .unwrap();
+
+    assert!(result.is_ok());
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(*retries.lock().unwrap(), vec![1, 2]);
+}
+
+#[test]
+fn retry_policy_reports_exhausted_attempts() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let a = attempts.clone();
+    let layer = quick_layer!("layer", "broken", Value, move |_args, _ctx| {
+        a.fetch_add(1, Ordering::SeqCst);
+        Err(execution_error!("permanent"))
+    });
+
+    let slice = Slice::builder("s1")
+        .layer("layer", |m| m.call_default("broken"))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .config(EngineConfig::new().retry(RetryPolicy::new(2, Duration::ZERO)))
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT_NO_OBSERVER);
+    let slice_results = results.get("s1").unwrap().as_ref().unwrap();
+    let error = slice_results
+        .method_results
+        .get(&method_key("layer", "broken"))
+        .unwrap()
+        .as_ref()
+        .unwrap_err();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    assert!(matches!(error, Error::RetriesExhausted { attempts: 2, .. }));
+    assert_eq!(error.root_cause().message(), "permanent");
+}
+
+#[test]
+fn build_rejects_unknown_layer() {
+    let layer = quick_layer!("layer", "work", Value, |_args, _ctx| { Ok(value!({})) });
+
+    let slice = Slice::builder("test")
+        .layer("layr", |m| m.call_default("work"))
+        .build();
+
+    let result = Engine::builder().add_layer(layer).add_slice(slice).build();
+
+    match result {
+        Err(Error::ConfigError(msg)) => assert!(msg.contains("test.layr.work"), "{}", msg),
+        _ => panic!("Expected a configuration error"),
+    }
+}
+
+#[test]
+fn build_rejects_unknown_method() {
+    let layer = quick_layer!("layer", "work", Value, |_args, _ctx| { Ok(value!({})) });
+
+    let slice = Slice::builder("test")
+        .layer("layer", |m| m.call_default("work").call_default("wrok"))
+        .build();
+
+    let result = Engine::builder().add_layer(layer).add_slice(slice).build();
+
+    match result {
+        Err(Error::ConfigError(msg)) => assert!(msg.contains("test.layer.wrok"), "{}", msg),
+        _ => panic!("Expected a configuration error"),
+    }
+}
+
+#[test]
+fn max_waves_per_slice_rejects_deep_chains() {
+    let names = ["l0", "l1", "l2", "l3", "l4"];
+
+    let mut builder = Engine::builder();
+    let mut slice = Slice::builder("chain");
+    for (i, name) in names.iter().enumerate() {
+        builder = builder.add_layer(quick_layer!(*name, "work", Value, |_args, _ctx| {
+            Ok(value!({}))
+        }));
+        if i > 0 {
+            builder = builder.dependency(*name, names[i - 1]);
+        }
+        slice = slice.layer(*name, |m| m.call_default("work"));
+    }
+
+    let engine = builder
+        .add_slice(slice.build())
+        .config(EngineConfig::new().max_waves_per_slice(3))
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    match results.get("chain").unwrap() {
+        Err(Error::ConfigError(msg)) => assert!(msg.contains("more than 3 waves"), "{}", msg),
+        other => panic!("Expected a wave limit error, got {:?}", other),
+    }
+}
+
+#[test]
+fn sequential_mode_never_overlaps() {
+    let concurrent = Arc::new(AtomicUsize::new(0));
+    let max_concurrent = Arc::new(AtomicUsize::new(0));
+
+    let c = concurrent.clone();
+    let mc = max_concurrent.clone();
+    let l1 = Layer::builder("l1")
+        .method("m1")
+        .args::<Value>()
+        .bind({
+            let c = c.clone();
+            let mc = mc.clone();
+            move |_args, _ctx| {
+                let current = c.fetch_add(1, Ordering::SeqCst) + 1;
+                mc.fetch_max(current, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(20));
+                c.fetch_sub(1, Ordering::SeqCst);
+                Ok(value!({}))
+            }
+        })
+        .method("m2")
+        .args::<Value>()
+        .bind(move |_args, _ctx| {
+            let current = c.fetch_add(1, Ordering::SeqCst) + 1;
+            mc.fetch_max(current, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            c.fetch_sub(1, Ordering::SeqCst);
+            Ok(value!({}))
+        })
+        .build();
+
+    let mut builder = Engine::builder()
+        .config(EngineConfig::new().sequential())
+        .add_layer(l1);
+    for name in ["s1", "s2", "s3"] {
+        builder = builder.add_slice(
+            Slice::builder(name)
+                .layer("l1", |methods| {
+                    methods.call_default("m1").call_default("m2")
+                })
+                .build(),
+        );
+    }
+    let engine = builder.build().unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(results.successful_slices(), 3);
+    assert_eq!(max_concurrent.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn conditional_dependency_applies_per_slice() {
+    let b = quick_layer!("b", "produce", Value, |_args, ctx| {
+        ctx.set("b_done", Value::from(true));
+        Ok(value!({}))
+    });
+
+    let c = quick_layer!("c", "consume", Value, |_args, ctx| {
+        Ok(Value::from(ctx.contains("b_done")))
+    });
+
+    // "with_b" waits for b; "without_b" never calls b, which would stall a static edge
+    let with_b = Slice::builder("with_b")
+        .seed("use_b", true)
+        .layer("b", |m| m.call_default("produce"))
+        .layer("c", |m| m.call_default("consume"))
+        .build();
+    let without_b = Slice::builder("without_b")
+        .layer("c", |m| m.call_default("consume"))
+        .build();
+
+    let engine = add_layers!(Engine::builder(), b, c)
+        .conditional_dependency("c", "b", |ctx| ctx.get("use_b").is_some())
+        .add_slice(with_b)
+        .add_slice(without_b)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    let with_b = results.get("with_b").unwrap().as_ref().unwrap();
+    assert_eq!(
+        with_b.method_results[&method_key("c", "consume")]
+            .as_ref()
+            .unwrap(),
+        &Value::from(true)
+    );
+
+    let without_b = results.get("without_b").unwrap().as_ref().unwrap();
+    assert_eq!(
+        without_b.method_results[&method_key("c", "consume")]
+            .as_ref()
+            .unwrap(),
+        &Value::from(false)
+    );
+}
+
+#[test]
+fn catch_panics_records_method_failure() {
+    let layer = Layer::builder("l1")
+        .method("explode")
+        .args::<Value>()
+        .bind(|_args, _ctx| panic!("boom"))
+        .method("fine")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(value!({ "ok": true })))
+        .build();
+
+    let bad = Slice::builder("bad")
+        .layer("l1", |m| m.call_default("explode").call_default("fine"))
+        .build();
+    let good = Slice::builder("good")
+        .layer("l1", |m| m.call_default("fine"))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(bad)
+        .add_slice(good)
+        .config(EngineConfig::new().catch_panics(true))
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    let bad = results.get("bad").unwrap().as_ref().unwrap();
+    let error = bad.method_results[&method_key("l1", "explode")]
+        .as_ref()
+        .unwrap_err();
+    match error.root_cause() {
+        Error::MethodPanicked { message } => assert_eq!(message, "boom"),
+        other => panic!("Expected a caught panic, got {:?}", other),
+    }
+    assert!(bad.method_results[&method_key("l1", "fine")].is_ok());
+
+    assert!(results.get("good").unwrap().is_ok());
+}
+
+#[test]
+fn run_slice_runs_only_the_named_slice() {
+    let runs = Arc::new(Mutex::new(Vec::new()));
+    let r = runs.clone();
+    let layer = quick_layer!("l1", "work", Value, move |args, _ctx| {
+        let name = args.get("name").unwrap().as_str().unwrap().to_string();
+        r.lock().unwrap().push(name.clone());
+        Ok(Value::from(name))
+    });
+
+    let mut builder = Engine::builder().add_layer(layer);
+    for name in ["s1", "s2", "s3"] {
+        builder = builder.add_slice(
+            Slice::builder(name)
+                .layer("l1", |m| m.call("work", value!({ "name": name })))
+                .build(),
+        );
+    }
+    let engine = builder.build().unwrap();
+
+    let results = engine.run_slice("s2", RunFlags::SILENT).unwrap();
+
+    assert_eq!(results.method_results.len(), 1);
+    assert_eq!(
+        results.method_results[&method_key("l1", "work")]
+            .as_ref()
+            .unwrap(),
+        &Value::from("s2")
+    );
+    assert_eq!(*runs.lock().unwrap(), vec!["s2".to_string()]);
+
+    match engine.run_slice("missing", RunFlags::SILENT) {
+        Err(Error::ConfigError(msg)) => assert!(msg.contains("missing")),
+        other => panic!("Expected a config error, got {:?}", other),
+    }
+}
+
+#[test]
+fn run_filtered_skips_unmatched_slices() {
+    let layer = quick_layer!("l1", "work", Value, |_args, _ctx| Ok(value!({})));
+
+    let mut builder = Engine::builder().add_layer(layer);
+    for name in ["a1", "a2", "b1"] {
+        builder = builder.add_slice(
+            Slice::builder(name)
+                .layer("l1", |m| m.call_default("work"))
+                .build(),
+        );
+    }
+    let engine = builder.build().unwrap();
+
+    let results = engine.run_filtered(|name| name.starts_with('a'), RunFlags::SILENT);
+
+    assert_eq!(results.len(), 2);
+    assert!(results.get("a1").unwrap().is_ok());
+    assert!(results.get("a2").unwrap().is_ok());
+    assert!(!results.contains_key("b1"));
+}
+
+#[test]
+fn rerun_failed_runs_only_the_failures() {
+    // Slices "flaky*" fail on their first call only
+    let calls = Arc::new(Mutex::new(std::collections::HashMap::<i64, usize>::new()));
+    let c = calls.clone();
+    let layer = quick_layer!("l1", "work", i64, move |&id, _ctx| {
+        let mut calls = c.lock().unwrap();
+        let count = calls.entry(id).or_default();
+        *count += 1;
+        if id > 0 && *count == 1 {
+            Err(execution_error!("flaky"))
+        } else {
+            Ok(value!(id))
+        }
+    });
+
+    let mut builder = Engine::builder().add_layer(layer);
+    for (name, id) in [("stable", 0), ("flaky1", 1), ("flaky2", 2)] {
+        builder = builder.add_slice(
+            Slice::builder(name)
+                .layer("l1", |m| m.call("work", id))
+                .build(),
+        );
+    }
+    let engine = builder.build().unwrap();
+
+    let mut results = engine.run(RunFlags::SILENT);
+    assert_eq!(results.failed_methods(), 2);
+
+    let rerun = engine.rerun_failed(&results, RunFlags::SILENT);
+    let mut names: Vec<&String> = rerun.keys().collect();
+    names.sort();
+    assert_eq!(names, ["flaky1", "flaky2"]);
+    assert!(rerun.is_all_success());
+
+    results.merge(rerun);
+    assert_eq!(results.len(), 3);
+    assert!(results.is_all_success());
+    assert_eq!(calls.lock().unwrap()[&0], 1);
+}
+
+#[test]
+fn high_priority_slices_start_first() {
+    let started = Arc::new(Mutex::new(Vec::new()));
+    let s = started.clone();
+    let layer = quick_layer!("l1", "work", Value, move |args, _ctx| {
+        s.lock().unwrap().push(args.as_str().unwrap().to_string());
+        std::thread::sleep(Duration::from_millis(5));
+        Ok(value!(null))
+    });
+
+    let mut builder = Engine::builder().add_layer(layer).num_threads(2);
+    for i in 0..20 {
+        let name = format!("bulk{}", i);
+        builder = builder.add_slice(
+            Slice::builder(name.clone())
+                .layer("l1", |m| m.call("work", name.as_str()))
+                .build(),
+        );
+    }
+    builder = builder.add_slice(
+        Slice::builder("urgent")
+            .priority(10)
+            .layer("l1", |m| m.call("work", "urgent"))
+            .build(),
+    );
+    let engine = builder.build().unwrap();
+
+    assert!(engine.run(RunFlags::SILENT).is_all_success());
+
+    let started = started.lock().unwrap();
+    let position = started.iter().position(|name| name == "urgent").unwrap();
+    assert!(
+        position < 2,
+        "urgent started at {}: {:?}",
+        position,
+        started
+    );
+}
+
+#[test]
+fn method_hooks_wrap_every_call() {
+    let layer = Layer::builder("db")
+        .method("read")
+        .args::<Value>()
+        .bind(|_args, ctx| Ok(Value::from(ctx.contains("tx"))))
+        .method("write")
+        .args::<Value>()
+        .bind(|_args, _ctx| Err(execution_error!("write failed")))
+        .build();
+
+    let slice = Slice::builder("s1")
+        .layer("db", |m| m.call_default("read").call_default("write"))
+        .build();
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let before_calls = calls.clone();
+    let after_calls = calls.clone();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .before_method(move |slice, layer, method, ctx| {
+            ctx.set("tx", Value::from(true));
+            before_calls
+                .lock()
+                .unwrap()
+                .push(format!("before {}.{}.{}", slice, layer, method));
+        })
+        .after_method(move |slice, layer, method, result, _ctx| {
+            after_calls.lock().unwrap().push(format!(
+                "after {}.{}.{} ok={}",
+                slice,
+                layer,
+                method,
+                result.is_ok()
+            ));
+        })
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    let s1 = results.get("s1").unwrap().as_ref().unwrap();
+    assert_eq!(
+        s1.method_results[&method_key("db", "read")]
+            .as_ref()
+            .unwrap(),
+        &Value::from(true)
+    );
+
+    let mut calls = calls.lock().unwrap().clone();
+    calls.sort();
+    assert_eq!(
+        calls,
+        vec![
+            "after s1.db.read ok=true",
+            "after s1.db.write ok=false",
+            "before s1.db.read",
+            "before s1.db.write",
+        ]
+    );
+}
+
+#[test]
+fn retry_failures_patches_recovered_methods() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let a = attempts.clone();
+    let layer = Layer::builder("net")
+        .method("flaky")
+        .args::<Value>()
+        .bind(move |_args, _ctx| {
+            if a.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(execution_error!("connection reset"))
+            } else {
+                Ok(value!({ "ok": true }))
+            }
+        })
+        .method("broken")
+        .args::<Value>()
+        .bind(|_args, _ctx| Err(execution_error!("always fails")))
+        .method("stable")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(value!({})))
+        .build();
+
+    let slice = Slice::builder("s1")
+        .layer("net", |m| {
+            m.call_default("flaky")
+                .call_default("broken")
+                .call_default("stable")
+        })
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .build()
+        .unwrap();
+
+    let mut results = engine.run(RunFlags::SILENT);
+    assert_eq!(results.failed_methods(), 2);
+
+    let recovered = engine.retry_failures(&mut results, RunFlags::SILENT);
+
+    assert_eq!(recovered, 1);
+    assert_eq!(results.failed_methods(), 1);
+    let s1 = results.get("s1").unwrap().as_ref().unwrap();
+    assert_eq!(
+        s1.method_results[&method_key("net", "flaky")]
+            .as_ref()
+            .unwrap(),
+        &value!({ "ok": true })
+    );
+    assert!(s1.method_results[&method_key("net", "broken")].is_err());
+}
+
+#[test]
+fn max_concurrent_slices_caps_in_flight_slices() {
+    let concurrent = Arc::new(AtomicUsize::new(0));
+    let max_concurrent = Arc::new(AtomicUsize::new(0));
+
+    let c = concurrent.clone();
+    let mc = max_concurrent.clone();
+    let l1 = Layer::builder("l1")
+        .method("m1")
+        .args::<Value>()
+        .bind(move |_args, _ctx| {
+            let current = c.fetch_add(1, Ordering::SeqCst) + 1;
+            mc.fetch_max(current, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(30));
+            c.fetch_sub(1, Ordering::SeqCst);
+            Ok(value!({}))
+        })
+        .build();
+
+    let mut builder = Engine::builder()
+        .add_layer(l1)
+        .config(EngineConfig::new().num_threads(4).max_concurrent_slices(2));
+    for i in 0..8 {
+        builder = builder.add_slice(
+            Slice::builder(format!("s{}", i))
+                .layer("l1", |methods| methods.call_default("m1"))
+                .build(),
+        );
+    }
+    let engine = builder.build().unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(results.successful_slices(), 8);
+    let observed = max_concurrent.load(Ordering::SeqCst);
+    assert!(observed <= 2, "Saw {} slices in flight", observed);
+}
+
+#[test]
+fn max_in_flight_caps_slow_slices() {
+    let concurrent = Arc::new(AtomicUsize::new(0));
+    let max_concurrent = Arc::new(AtomicUsize::new(0));
+
+    let c = concurrent.clone();
+    let mc = max_concurrent.clone();
+    let l1 = Layer::builder("l1")
+        .method("m1")
+        .args::<Value>()
+        .bind(move |_args, _ctx| {
+            let current = c.fetch_add(1, Ordering::SeqCst) + 1;
+            mc.fetch_max(current, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            c.fetch_sub(1, Ordering::SeqCst);
+            Ok(value!({}))
+        })
+        .build();
+
+    let mut builder = Engine::builder()
+        .add_layer(l1)
+        .config(EngineConfig::new().num_threads(6).max_in_flight(2));
+    for i in 0..6 {
+        builder = builder.add_slice(
+            Slice::builder(format!("s{}", i))
+                .layer("l1", |methods| methods.call_default("m1"))
+                .build(),
+        );
+    }
+    let engine = builder.build().unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(results.successful_slices(), 6);
+    let observed = max_concurrent.load(Ordering::SeqCst);
+    assert!(observed <= 2, "Saw {} slices in flight", observed);
+}
+
+#[test]
+fn plan_reports_waves_without_running() {
+    let ran = Arc::new(AtomicUsize::new(0));
+    let layer = |name: &str, method: &str| {
+        let ran = ran.clone();
+        Layer::builder(name)
+            .method(method)
+            .args::<Value>()
+            .bind(move |_args, _ctx| {
+                ran.fetch_add(1, Ordering::SeqCst);
+                Ok(value!({}))
+            })
+            .build()
+    };
+
+    let slice = Slice::builder("etl")
+        .layer("extract", |m| m.call_default("load"))
+        .layer("transform", |m| m.call_default("process"))
+        .layer("load", |m| m.call_default("save"))
+        .build();
+
+    let engine = dependencies!(
+        add_layers!(
+            Engine::builder(),
+            layer("extract", "load"),
+            layer("transform", "process"),
+            layer("load", "save")
+        ),
+        "transform" => ["extract"],
+        "load" => ["transform"]
+    )
+    .add_slice(slice)
+    .build()
+    .unwrap();
+
+    let plan = engine.plan().unwrap();
+
+    assert_eq!(plan.execution_order, vec!["extract", "transform", "load"]);
+    let waves = plan.waves("etl").unwrap();
+    assert_eq!(waves.len(), 3);
+    assert_eq!(waves[0], vec![method_key("extract", "load")]);
+    assert_eq!(waves.last().unwrap(), &vec![method_key("load", "save")]);
+    assert_eq!(ran.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn plan_matches_run_for_conditional_edges_on_the_shared_context() {
+    let b = quick_layer!("b", "m", Value, |_args, _ctx| Ok(Value::Null));
+    let c = quick_layer!("c", "m", Value, |_args, _ctx| Ok(Value::Null));
+
+    let shared = Context::new();
+    shared.set("use_b", value!(true));
+
+    let waves_run = Arc::new(AtomicUsize::new(0));
+    let w = waves_run.clone();
+
+    let engine = Engine::builder()
+        .add_layer(b)
+        .add_layer(c)
+        .conditional_dependency("c", "b", |ctx| ctx.contains("use_b"))
+        .with_shared_context(shared)
+        .add_slice(
+            Slice::builder("s")
+                .layer("b", |m| m.call_default("m"))
+                .layer("c", |m| m.call_default("m"))
+                .build(),
+        )
+        .observe(move |observer| {
+            observer.on_event(move |event| {
+                if let EngineEvent::WaveStart { .. } = event {
+                    w.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        })
+        .build()
+        .unwrap();
+
+    let plan = engine.plan().unwrap();
+    let waves = plan.waves("s").unwrap();
+    assert_eq!(
+        waves,
+        &vec![vec![method_key("b", "m")], vec![method_key("c", "m")],]
+    );
+
+    assert!(engine.run(RunFlags::SILENT).is_all_success());
+    assert_eq!(waves_run.load(Ordering::SeqCst), waves.len());
+}
+
+#[test]
+fn conditional_edges_ignore_writes_made_during_the_slice() {
+    // "a" sets the flag the edge checks, but the edge was decided before "a" ran
+    let a = quick_layer!("a", "m", Value, |_args, ctx| {
+        ctx.set("use_b", value!(true));
+        Ok(Value::Null)
+    });
+    let b = quick_layer!("b", "m", Value, |_args, _ctx| Ok(Value::Null));
+    let c = quick_layer!("c", "m", Value, |_args, _ctx| Ok(Value::Null));
+
+    let waves_run = Arc::new(AtomicUsize::new(0));
+    let w = waves_run.clone();
+
+    let engine = add_layers!(Engine::builder(), a, b, c)
+        .dependency("b", "a")
+        .dependency("c", "a")
+        .conditional_dependency("c", "b", |ctx| ctx.contains("use_b"))
+        .add_slice(
+            Slice::builder("s")
+                .layer("a", |m| m.call_default("m"))
+                .layer("b", |m| m.call_default("m"))
+                .layer("c", |m| m.call_default("m"))
+                .build(),
+        )
+        .observe(move |observer| {
+            observer.on_event(move |event| {
+                if let EngineEvent::WaveStart { .. } = event {
+                    w.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        })
+        .build()
+        .unwrap();
+
+    let plan = engine.plan().unwrap();
+    let waves = plan.waves("s").unwrap();
+    assert_eq!(
+        waves,
+        &vec![
+            vec![method_key("a", "m")],
+            vec![method_key("b", "m"), method_key("c", "m")],
+        ]
+    );
+
+    assert!(engine.run(RunFlags::SILENT).is_all_success());
+    assert_eq!(waves_run.load(Ordering::SeqCst), waves.len());
+}
+
+#[test]
+fn plan_surfaces_wave_errors() {
+    let names = ["l0", "l1", "l2"];
+
+    let mut builder = Engine::builder();
+    let mut slice = Slice::builder("chain");
+    for (i, name) in names.iter().enumerate() {
+        builder = builder.add_layer(quick_layer!(*name, "work", Value, |_args, _ctx| {
+            Ok(value!({}))
+        }));
+        if i > 0 {
+            builder = builder.dependency(*name, names[i - 1]);
+        }
+        slice = slice.layer(*name, |m| m.call_default("work"));
+    }
+
+    let engine = builder
+        .add_slice(slice.build())
+        .config(EngineConfig::new().max_waves_per_slice(2))
+        .build()
+        .unwrap();
+
+    match engine.plan() {
+        Err(Error::ConfigError(msg)) => assert!(msg.contains("more than 2 waves"), "{}", msg),
+        other => panic!("Expected a wave limit error, got {:?}", other),
+    }
+}
+
+#[test]
+fn mutually_exclusive_layers_are_validated() {
+    let build = |slice: Slice| {
+        add_layers!(
+            Engine::builder(),
+            quick_layer!("fast_path", "run", Value, |_args, _ctx| Ok(value!({}))),
+            quick_layer!("slow_path", "run", Value, |_args, _ctx| Ok(value!({})))
+        )
+        .mutually_exclusive(&["fast_path", "slow_path"])
+        .add_slice(slice)
+        .build()
+    };
+
+    let both = Slice::builder("both")
+        .layer("fast_path", |m| m.call_default("run"))
+        .layer("slow_path", |m| m.call_default("run"))
+        .build();
+
+    match build(both) {
+        Err(Error::ConfigError(msg)) => {
+            assert!(msg.contains("'both'"), "{}", msg);
+            assert!(msg.contains("fast_path, slow_path"), "{}", msg);
+        }
+        _ => panic!("Expected a configuration error"),
+    }
+
+    let one = Slice::builder("one")
+        .layer("slow_path", |m| m.call_default("run"))
+        .build();
+
+    assert!(build(one).is_ok());
+}
+
+#[test]
+fn to_dot_exports_dependency_graph() {
+    let engine = add_layers!(
+        Engine::builder(),
+        quick_layer!("init", "setup", Value, |_args, _ctx| Ok(value!({}))),
+        quick_layer!("Layer_A", "Work_A", Value, |_args, _ctx| Ok(value!({}))),
+        quick_layer!("Layer_B", "Work_B", Value, |_args, _ctx| Ok(value!({}))),
+        quick_layer!("say \"hi\"", "Work_C", Value, |_args, _ctx| Ok(value!({})))
+    )
+    .init_layer("init")
+    .dependency("Layer_B", "Layer_A")
+    .conditional_dependency("say \"hi\"", "Layer_B", |_ctx| true)
+    .build()
+    .unwrap();
+
+    let dot = engine.to_dot();
+
+    assert!(dot.starts_with("digraph sandl {\n"), "{}", dot);
+    assert!(dot.contains("\"Layer_A\" -> \"Layer_B\";"), "{}", dot);
+    assert!(dot.contains("\"init\" [shape=box];"), "{}", dot);
+    assert!(dot.contains("\"init\" -> \"Layer_A\";"), "{}", dot);
+    assert!(
+        dot.contains("\"Layer_B\" -> \"say \\\"hi\\\"\" [style=dashed];"),
+        "{}",
+        dot
+    );
+    assert_eq!(dot, engine.to_dot());
+}
+
+#[test]
+fn quorum_skips_the_rest_of_the_wave() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let replicas = ["r1", "r2", "r3"]
+        .iter()
+        .fold(Layer::builder("replicas"), |builder, name| {
+            let calls = calls.clone();
+            builder
+                .method(*name)
+                .args::<Value>()
+                .bind(move |_args, _ctx| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Ok(value!(42))
+                })
+        })
+        .build();
+
+    let consume = Layer::builder("consume")
+        .method("pick")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(value!("done")))
+        .build();
+
+    let slice = Slice::builder("s")
+        .layer("replicas", |m| {
+            m.call_default("r1").call_default("r2").call_default("r3")
+        })
+        .layer("consume", |m| m.call_default("pick"))
+        .quorum(2)
+        .build();
+
+    let engine = Engine::builder()
+        .config(EngineConfig::new().sequential())
+        .add_layer(replicas)
+        .add_layer(consume)
+        .dependency("consume", "replicas")
+        .add_slice(slice)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let slice_results = results.get("s").unwrap().as_ref().unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(slice_results.method_results.len(), 3);
+    assert!(slice_results.is_skipped("replicas", "r3"));
+    assert!(
+        !slice_results
+            .method_results
+            .contains_key(&method_key("replicas", "r3"))
+    );
+    assert_eq!(
+        slice_results.method_results[&method_key("consume", "pick")]
+            .as_ref()
+            .unwrap(),
+        &value!("done")
+    );
+}
+
+#[test]
+fn quorum_discards_a_slow_replica_in_parallel_but_waits_for_it() {
+    const SLOW: Duration = Duration::from_millis(100);
+    // r1 and r2 finish only once r3 is running, and r3 only once they're done, so r3
+    // is always in flight when the quorum is reached
+    let slow_started = Arc::new(AtomicUsize::new(0));
+    let fast_done = Arc::new(AtomicUsize::new(0));
+    let wait_for = |counter: &AtomicUsize, target: usize| {
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while counter.load(Ordering::SeqCst) < target && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    };
+
+    let mut replicas = Layer::builder("replicas");
+    for name in ["r1", "r2"] {
+        let (slow_started, fast_done) = (slow_started.clone(), fast_done.clone());
+        replicas = replicas
+            .method(name)
+            .args::<Value>()
+            .bind(move |_args, _ctx| {
+                wait_for(&slow_started, 1);
+                fast_done.fetch_add(1, Ordering::SeqCst);
+                Ok(value!(42))
+            });
+    }
+    let fast = fast_done.clone();
+    let replicas = replicas
+        .method("r3")
+        .args::<Value>()
+        .bind(move |_args, _ctx| {
+            slow_started.fetch_add(1, Ordering::SeqCst);
+            wait_for(&fast, 2);
+            std::thread::sleep(SLOW);
+            Ok(value!(42))
+        })
+        .build();
+
+    let slice = Slice::builder("s")
+        .layer("replicas", |m| {
+            m.call_default("r1").call_default("r2").call_default("r3")
+        })
+        .quorum(2)
+        .build();
+
+    let engine = Engine::builder()
+        .config(EngineConfig::new().num_threads(3))
+        .add_layer(replicas)
+        .add_slice(slice)
+        .build()
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    let results = engine.run(RunFlags::SILENT);
+    let elapsed = start.elapsed();
+    let slice_results = results.get("s").unwrap().as_ref().unwrap();
+
+    assert_eq!(fast_done.load(Ordering::SeqCst), 2);
+    assert!(slice_results.method_results[&method_key("replicas", "r1")].is_ok());
+    assert!(slice_results.method_results[&method_key("replicas", "r2")].is_ok());
+    assert!(slice_results.is_skipped("replicas", "r3"));
+    assert!(elapsed >= SLOW, "{:?}", elapsed);
+}
+
+#[test]
+fn quorum_cancels_replicas_that_poll_the_wave() {
+    const SLOW: Duration = Duration::from_secs(5);
+    let slow_started = Arc::new(AtomicUsize::new(0));
+
+    let mut replicas = Layer::builder("replicas");
+    for name in ["r1", "r2"] {
+        let slow_started = slow_started.clone();
+        replicas = replicas
+            .method(name)
+            .args::<Value>()
+            .bind(move |_args, _ctx| {
+                // Only finish once r3 is in flight, so it's the one the quorum cuts off
+                let deadline = std::time::Instant::now() + SLOW;
+                while slow_started.load(Ordering::SeqCst) == 0
+                    && std::time::Instant::now() < deadline
+                {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                Ok(value!(42))
+            });
+    }
+    let replicas = replicas
+        .method("r3")
+        .args::<Value>()
+        .bind(move |_args, ctx| {
+            slow_started.fetch_add(1, Ordering::SeqCst);
+            let deadline = std::time::Instant::now() + SLOW;
+            while std::time::Instant::now() < deadline {
+                if ctx.is_wave_cancelled() {
+                    return Err(execution_error!("gave up"));
+                }
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            Ok(value!(42))
+        })
+        .build();
+
+    let slice = Slice::builder("s")
+        .layer("replicas", |m| {
+            m.call_default("r1").call_default("r2").call_default("r3")
+        })
+        .quorum(2)
+        .build();
+
+    let engine = Engine::builder()
+        .config(EngineConfig::new().num_threads(3))
+        .add_layer(replicas)
+        .add_slice(slice)
+        .build()
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    let results = engine.run(RunFlags::SILENT);
+    let elapsed = start.elapsed();
+    let slice_results = results.get("s").unwrap().as_ref().unwrap();
+
+    assert!(elapsed < SLOW / 2, "{:?}", elapsed);
+    assert!(slice_results.method_results[&method_key("replicas", "r1")].is_ok());
+    assert!(slice_results.method_results[&method_key("replicas", "r2")].is_ok());
+    assert!(slice_results.is_skipped("replicas", "r3"));
+}
+
+#[test]
+fn on_run_end_fires_for_success_and_cancellation() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let layer = Layer::builder("l")
+        .method("m")
+        .args::<Value>()
+        .bind(|args, _ctx| {
+            if args.get("fail").is_some() {
+                Err(execution_error!("boom"))
+            } else {
+                Ok(value!(1))
+            }
+        })
+        .build();
+
+    let engine = Engine::builder()
+        .config(EngineConfig::new().sequential())
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("a")
+                .layer("l", |m| m.call("m", value!({ "fail": true })))
+                .build(),
+        )
+        .add_slice(
+            Slice::builder("b")
+                .layer("l", |m| m.call("m", value!({})))
+                .build(),
+        )
+        .on_run_end({
+            let seen = seen.clone();
+            move |results| {
+                seen.lock()
+                    .unwrap()
+                    .push((results.len(), results.successful_slices()));
+            }
+        })
+        .build()
+        .unwrap();
+
+    engine.run(RunFlags::SILENT);
+    engine.run(RunFlags::SILENT.fail_fast());
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 2);
+    assert_eq!(seen[0], (2, 2));
+    assert_eq!(seen[1].0, 2);
+    assert!(seen[1].1 < 2);
+}
+
+#[test]
+fn on_run_end_fires_when_the_run_panics() {
+    let fired = Arc::new(AtomicUsize::new(0));
+
+    let engine = Engine::builder()
+        .add_layer(quick_layer!("l", "m", Value, |_args,
+                                                  _ctx|
+         -> Result<Value> {
+            panic!("method blew up")
+        }))
+        .add_slice(
+            Slice::builder("s")
+                .layer("l", |m| m.call_default("m"))
+                .build(),
+        )
+        .on_run_end({
+            let fired = fired.clone();
+            move |results| {
+                assert!(results.is_empty());
+                fired.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .build()
+        .unwrap();
+
+    let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        engine.run(RunFlags::SILENT);
+    }));
+
+    assert!(outcome.is_err());
+    assert_eq!(fired.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn once_layer_calls_use_engine_policies() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let a = attempts.clone();
+    let flaky = quick_layer!("lookup", "load", Value, move |_args, ctx| {
+        if a.fetch_add(1, Ordering::SeqCst) == 0 {
+            return Err(execution_error!("first attempt fails"));
+        }
+        ctx.set("table", value!(1));
+        Ok(Value::Null)
+    });
+    let work = quick_layer!("work", "read", Value, |_args, ctx| {
+        Ok(ctx.get("table").unwrap_or(Value::Null))
+    });
+    let before = Arc::new(AtomicUsize::new(0));
+
+    let engine = Engine::builder()
+        .add_layer(flaky)
+        .add_layer(work)
+        .once_layer("lookup")
+        .before_method({
+            let before = before.clone();
+            move |_slice, layer, _method, _ctx| {
+                if layer == "lookup" {
+                    before.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        })
+        .config(EngineConfig::new().retry(RetryPolicy::new(2, Duration::ZERO)))
+        .add_slice(
+            Slice::builder("s")
+                .layer("work", |m| m.call_default("read"))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    assert_eq!(
+        results
+            .get_result("s", "work", "read")
+            .unwrap()
+            .as_ref()
+            .unwrap(),
+        &value!(1)
+    );
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    assert_eq!(before.load(Ordering::SeqCst), 1);
+
+    // With catch_panics, a panicking once method fails the slices instead of the run
+    let engine = Engine::builder()
+        .add_layer(quick_layer!("lookup", "load", Value, |_args, _ctx| {
+            panic!("lookup blew up")
+        }))
+        .add_layer(quick_layer!("work", "read", Value, |_args, _ctx| Ok(
+            Value::Null
+        )))
+        .once_layer("lookup")
+        .config(EngineConfig::new().catch_panics(true))
+        .add_slice(
+            Slice::builder("s")
+                .layer("work", |m| m.call_default("read"))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let error = results.get("s").unwrap().as_ref().err().unwrap();
+    assert!(error.to_string().contains("lookup blew up"), "{}", error);
+}
+
+#[test]
+fn once_layer_runs_a_single_time() {
+    let loads = Arc::new(AtomicUsize::new(0));
+
+    let l = loads.clone();
+    let lookup = quick_layer!("lookup", "load", Value, move |_args, ctx| {
+        l.fetch_add(1, Ordering::SeqCst);
+        ctx.set("table", value!({ "a": 1, "b": 2 }));
+        Ok(Value::Null)
+    });
+
+    let work = quick_layer!("work", "read", Value, |args, ctx| {
+        let key = args.get("key").unwrap().as_str().unwrap().to_string();
+        let table = ctx.get("table").unwrap();
+        Ok(table.get(&key).cloned().unwrap_or(Value::Null))
+    });
+
+    let mut builder = Engine::builder()
+        .add_layer(lookup)
+        .add_layer(work)
+        .once_layer("lookup");
+    for (i, key) in ["a", "b", "a", "b", "a"].iter().enumerate() {
+        builder = builder.add_slice(
+            Slice::builder(format!("s{}", i))
+                .layer("work", |m| m.call("read", value!({ "key": (*key) })))
+                .build(),
+        );
+    }
+    let engine = builder.build().unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    engine.run(RunFlags::SILENT);
+
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+    assert_eq!(results.failed_methods(), 0);
+    let s1 = results.get("s1").unwrap().as_ref().unwrap();
+    assert_eq!(
+        s1.method_results[&method_key("work", "read")]
+            .as_ref()
+            .unwrap(),
+        &value!(2)
+    );
+
+    let err = Engine::builder()
+        .add_layer(quick_layer!("lookup", "load", Value, |_args, _ctx| Ok(
+            Value::Null
+        )))
+        .once_layer("lookup")
+        .add_slice(
+            Slice::builder("s")
+                .layer("lookup", |m| m.call_default("load"))
+                .build(),
+        )
+        .build()
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("once layer 'lookup'"));
+}
+
+#[test]
+fn run_streaming_sends_every_slice() {
+    let layer = quick_layer!("l", "m", Value, |args, _ctx| Ok(args.clone()));
+
+    let mut builder = Engine::builder()
+        .config(EngineConfig::new().num_threads(4).stream_capacity(4))
+        .add_layer(layer);
+    for i in 0..50 {
+        builder = builder.add_slice(
+            Slice::builder(format!("s{}", i))
+                .layer("l", |m| m.call("m", value!({ "i": i })))
+                .build(),
+        );
+    }
+    let engine = Arc::new(builder.build().unwrap());
+
+    let mut names = std::collections::HashSet::new();
+    let mut total = 0;
+    for (name, result) in engine.run_streaming(RunFlags::SILENT) {
+        let slice_results = result.unwrap();
+        let value = slice_results.method_results[&method_key("l", "m")]
+            .as_ref()
+            .unwrap();
+        total += value.get("i").unwrap().as_i64().unwrap();
+        names.insert(name);
+    }
+
+    assert_eq!(names.len(), 50);
+    assert_eq!(total, (0..50).sum::<i64>());
+}
+
+#[test]
+fn run_streaming_emits_run_events_and_fires_run_end_hooks() {
+    let layer = quick_layer!("l", "m", Value, |_args, _ctx| Ok(Value::Null));
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let hook_calls = Arc::new(AtomicUsize::new(0));
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("s")
+                .layer("l", |m| m.call_default("m"))
+                .build(),
+        )
+        .observe({
+            let events = events.clone();
+            move |observer| {
+                observer.on_event(move |event| match event {
+                    EngineEvent::RunStart { total_slices, .. } => events
+                        .lock()
+                        .unwrap()
+                        .push(format!("start {}", total_slices)),
+                    EngineEvent::RunComplete { .. } => {
+                        events.lock().unwrap().push("complete".to_string())
+                    }
+                    _ => {}
+                });
+            }
+        })
+        .on_run_end({
+            let hook_calls = hook_calls.clone();
+            move |_results| {
+                hook_calls.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .build()
+        .unwrap();
+    let engine = Arc::new(engine);
+
+    // The channel closes only after the run has finished, hooks included
+    assert_eq!(engine.run_streaming(RunFlags::SILENT).iter().count(), 1);
+
+    assert_eq!(*events.lock().unwrap(), vec!["start 1", "complete"]);
+    assert_eq!(hook_calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn circular_dependency_names_the_cycle() {
+    let noop = |name: &str| quick_layer!(name, "m", Value, |_args, _ctx| Ok(Value::Null));
+
+    let engine = Engine::builder()
+        .add_layer(noop("a"))
+        .add_layer(noop("b"))
+        .add_layer(noop("c"))
+        .add_layer(noop("d"))
+        .dependency("a", "b")
+        .dependency("b", "c")
+        .dependency("c", "a")
+        .dependency("d", "a")
+        .build()
+        .unwrap();
+
+    // `d` is stuck behind the cycle but isn't part of it
+    let err = engine.topological_sort().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Circular dependency: a -> c -> b -> a"
+    );
+}
+
+#[test]
+fn duplicate_dependencies_are_deduplicated() {
+    let init = quick_layer!("init", "setup", Value, |_args, ctx| {
+        ctx.set("ready", Value::from(true));
+        Ok(Value::Null)
+    });
+    let a = quick_layer!("a", "m", Value, |_args, _ctx| Ok(value!(1)));
+    let b = quick_layer!("b", "m", Value, |_args, ctx| {
+        Ok(Value::from(ctx.get_as::<bool>("ready")?))
+    });
+
+    let engine = dependencies!(
+        Engine::builder()
+            .add_layer(init)
+            .add_layer(a)
+            .add_layer(b)
+            .init_layer("init"),
+        "b" => ["a", "a", "init"]
+    )
+    .dependency("b", "a")
+    .add_slice(
+        Slice::builder("s")
+            .layer("init", |m| m.call_default("setup"))
+            .layer("a", |m| m.call_default("m"))
+            .layer("b", |m| m.call_default("m"))
+            .build(),
+    )
+    .build()
+    .unwrap();
+
+    assert_eq!(engine.topological_sort().unwrap(), vec!["init", "a", "b"]);
+    assert_eq!(engine.to_dot().matches("\"a\" -> \"b\"").count(), 1);
+
+    let results = engine.run(RunFlags::SILENT);
+    assert_eq!(results.failed_methods(), 0);
+}
+
+#[test]
+fn unused_layer_with_missing_dependency_is_layer_not_found() {
+    let used = quick_layer!("used", "m", Value, |_args, _ctx| Ok(Value::Null));
+    let unused = quick_layer!("unused", "m", Value, |_args, _ctx| Ok(Value::Null));
+
+    let err = Engine::builder()
+        .add_layer(used)
+        .add_layer(unused)
+        .dependency("unused", "missing")
+        .add_slice(
+            Slice::builder("s")
+                .layer("used", |m| m.call_default("m"))
+                .build(),
+        )
+        .build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(err, Error::LayerNotFound(ref name) if name == "missing"));
+}
+
+#[test]
+fn unknown_dependent_layer_is_layer_not_found() {
+    let a = quick_layer!("a", "m", Value, |_args, _ctx| Ok(Value::Null));
+    let slice = || {
+        Slice::builder("s")
+            .layer("a", |m| m.call_default("m"))
+            .build()
+    };
+
+    let err = Engine::builder()
+        .add_layer(a.clone())
+        .dependency("ghost", "a")
+        .add_slice(slice())
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(err, Error::LayerNotFound(ref name) if name == "ghost"));
+
+    let err = Engine::builder()
+        .add_layer(a)
+        .conditional_dependency("ghost", "a", |_ctx| true)
+        .add_slice(slice())
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(err, Error::LayerNotFound(ref name) if name == "ghost"));
+}
+
+#[test]
+fn layer_max_concurrency_limits_calls_across_slices() {
+    let concurrent = Arc::new(AtomicUsize::new(0));
+    let max_concurrent = Arc::new(AtomicUsize::new(0));
+
+    let c = concurrent.clone();
+    let mc = max_concurrent.clone();
+    let limited = Layer::builder("api")
+        .max_concurrency(2)
+        .method("call")
+        .args::<Value>()
+        .bind(move |_args, _ctx| {
+            let current = c.fetch_add(1, Ordering::SeqCst) + 1;
+            mc.fetch_max(current, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            c.fetch_sub(1, Ordering::SeqCst);
+            Ok(value!({}))
+        })
+        .build();
+
+    let mut builder = Engine::builder()
+        .config(EngineConfig::new().num_threads(6))
+        .add_layer(limited);
+    for i in 0..6 {
+        builder = builder.add_slice(
+            Slice::builder(format!("s{}", i))
+                .layer("api", |m| m.call_default("call"))
+                .build(),
+        );
+    }
+    let engine = builder.build().unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(results.failed_methods(), 0);
+    assert!(max_concurrent.load(Ordering::SeqCst) <= 2);
+}
+
+#[test]
+fn runs_reuse_the_thread_pool() {
+    let threads = Arc::new(Mutex::new(Vec::new()));
+    let t = threads.clone();
+
+    let layer = quick_layer!("layer", "work", Value, move |_args, _ctx| {
+        t.lock().unwrap().push(std::thread::current().id());
+        Ok(value!(null))
+    });
+
+    let slice = Slice::builder("s")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+
+    let mut engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    assert!(engine.run(RunFlags::SILENT).is_all_success());
+    assert!(engine.run(RunFlags::SILENT).is_all_success());
+
+    // Changing the pool settings builds a new pool
+    engine.config.num_threads = Some(2);
+    assert!(engine.run(RunFlags::SILENT).is_all_success());
+
+    let threads = threads.lock().unwrap();
+    assert_eq!(threads.len(), 3);
+    assert_eq!(threads[0], threads[1]);
+    assert_ne!(threads[0], std::thread::current().id());
+    assert_ne!(threads[2], threads[0]);
+}
+
+#[test]
+fn pool_threads_are_named_after_the_prefix() {
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let n = names.clone();
+
+    let layer = quick_layer!("layer", "work", Value, move |_args, _ctx| {
+        let name = std::thread::current().name().map(str::to_string);
+        n.lock().unwrap().push(name);
+        Ok(value!(null))
+    });
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("s")
+                .layer("layer", |m| m.call_default("work"))
+                .build(),
+        )
+        .config(
+            EngineConfig::new()
+                .num_threads(2)
+                .thread_name_prefix("sandl-test"),
+        )
+        .build()
+        .unwrap();
+
+    assert!(engine.run(RunFlags::SILENT).is_all_success());
+
+    let names = names.lock().unwrap();
+    assert_eq!(names.len(), 1);
+    let name = names[0].as_deref().unwrap();
+    assert!(name.starts_with("sandl-test-"), "{}", name);
+}
+
+#[test]
+fn call_if_skips_methods_whose_gate_is_false() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let c = calls.clone();
+
+    let setup = quick_layer!("setup", "flags", Value, |_args, ctx| {
+        ctx.set("enrich", value!(false));
+        Ok(value!(null))
+    });
+
+    let work = Layer::builder("work")
+        .method("enrich")
+        .args::<Value>()
+        .bind(move |_args, _ctx| {
+            c.fetch_add(1, Ordering::SeqCst);
+            Ok(value!("enriched"))
+        })
+        .method("store")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(value!("stored")))
+        .build();
+
+    let slice = Slice::builder("s")
+        .layer("setup", |m| m.call_default("flags"))
+        .layer("work", |m| {
+            m.call_if("enrich", Value::Null, |ctx| {
+                ctx.get("enrich").and_then(|v| v.as_bool()).unwrap_or(false)
+            })
+            .call_default("store")
+        })
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(setup)
+        .add_layer(work)
+        .dependency("work", "setup")
+        .add_slice(slice)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let slice_results = results.get("s").unwrap().as_ref().unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+    assert!(slice_results.is_skipped("work", "enrich"));
+    assert!(results.get_result("s", "work", "enrich").is_none());
+    assert_eq!(
+        results.get_typed::<String>("s", "work", "store").unwrap(),
+        "stored"
+    );
+}
+
+#[test]
+fn run_iter_pulls_slices_in_batches() {
+    let produced = Arc::new(AtomicUsize::new(0));
+    let max_ahead = Arc::new(AtomicUsize::new(0));
+    let done = Arc::new(AtomicUsize::new(0));
+
+    let (p, m, d) = (produced.clone(), max_ahead.clone(), done.clone());
+    let layer = quick_layer!("layer", "square", Value, move |args, _ctx| {
+        // Slices built but not yet finished never exceed one batch
+        let finished = d.fetch_add(1, Ordering::SeqCst);
+        m.fetch_max(p.load(Ordering::SeqCst) - finished, Ordering::SeqCst);
+
+        let n = args.get("n").and_then(|v| v.as_i64()).unwrap();
+        Ok(value!(n * n))
+    });
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .config(EngineConfig::new().batch_size(100))
+        .build()
+        .unwrap();
+
+    let generator = (0..10_000).map(|n| {
+        produced.fetch_add(1, Ordering::SeqCst);
+        Slice::builder(format!("s{}", n))
+            .layer("layer", |m| m.call("square", value!({ "n": n })))
+            .build()
+    });
+
+    let results = engine.run_iter(generator, RunFlags::SILENT);
+
+    assert_eq!(results.len(), 10_000);
+    assert!(results.is_all_success());
+    assert_eq!(
+        results
+            .get_typed::<i64>("s9999", "layer", "square")
+            .unwrap(),
+        9999 * 9999
+    );
+    assert!(max_ahead.load(Ordering::SeqCst) <= 100);
+}
+
+#[test]
+fn method_descriptions_are_listed() {
+    let fetch = Layer::builder("fetch")
+        .method("download")
+        .describe("Downloads one page")
+        .args_with_default(value!({ "retries": 3 }))
+        .bind(|_args, _ctx| Ok(value!(null)))
+        .method("parse")
+        .args::<Value>()
+        .bind_pure(|_args| Ok(value!(null)))
+        .build();
+
+    assert_eq!(
+        fetch.method_description("download"),
+        Some("Downloads one page")
+    );
+    assert_eq!(fetch.method_description("parse"), None);
+
+    let engine = Engine::builder().add_layer(fetch).build().unwrap();
+    assert_eq!(
+        engine.describe(),
+        "fetch\n  download {\"retries\":3} - Downloads one page\n  parse null\n"
+    );
+}
+
+#[test]
+fn strict_build_rejects_unbound_methods() {
+    let layer = || {
+        let mut layer = Layer::new("io".to_string())
+            .with_method(MethodConfig {
+                name: "read".to_string(),
+                default: Value::Null,
+            })
+            .with_method(MethodConfig {
+                name: "write".to_string(),
+                default: Value::Null,
+            });
+        layer.bind("read", |_args, _ctx| Ok(value!(null))).unwrap();
+        layer
+    };
+
+    assert_eq!(layer().unbound_methods(), vec!["write"]);
+
+    assert!(Engine::builder().add_layer(layer()).build().is_ok());
+
+    let err = Engine::builder()
+        .add_layer(layer())
+        .strict()
+        .build()
+        .err()
+        .unwrap();
+    assert!(
+        matches!(err, Error::MethodNotBound(method, layer) if method == "write" && layer == "io")
+    );
+}
+
+#[test]
+fn cloned_layers_and_slices_run_independently() {
+    let double = quick_layer!("double", "apply", Value, |args, _ctx| {
+        let n = args.get("n").and_then(|v| v.as_i64()).unwrap();
+        Ok(value!(n * 2))
+    });
+
+    let mut copy = double.clone();
+    copy.name = "double_again".to_string();
+
+    let base = Slice::builder("base")
+        .layer("double", |m| m.call("apply", value!({ "n": 1 })))
+        .layer("double_again", |m| m.call("apply", value!({ "n": 1 })))
+        .build();
+
+    // A template slice with one method's args overridden
+    let mut tweaked = base.clone();
+    tweaked.name = "tweaked".to_string();
+    tweaked
+        .methods_per_layer
+        .get_mut("double_again")
+        .unwrap()
+        .insert("apply".to_string(), value!({ "n": 21 }));
+
+    let engine = add_slices!(
+        Engine::builder().add_layer(double).add_layer(copy),
+        base,
+        tweaked
+    )
+    .build()
+    .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(
+        results.get_typed::<i64>("base", "double", "apply").unwrap(),
+        2
+    );
+    assert_eq!(
+        results
+            .get_typed::<i64>("base", "double_again", "apply")
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        results
+            .get_typed::<i64>("tweaked", "double", "apply")
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        results
+            .get_typed::<i64>("tweaked", "double_again", "apply")
+            .unwrap(),
+        42
+    );
+}
+
+#[test]
+fn to_builder_derives_a_slice_from_a_template() {
+    let layer = Layer::builder("alloc")
+        .method("size")
+        .args::<Value>()
+        .bind(|args, _ctx| Ok(args.clone()))
+        .method("label")
+        .args::<Value>()
+        .bind(|args, _ctx| Ok(args.clone()))
+        .build();
+
+    let base = Slice::builder("base")
+        .layer("alloc", |m| {
+            m.call("size", value!(1)).call("label", value!("same"))
+        })
+        .build();
+
+    let derived = base
+        .to_builder()
+        .name("derived")
+        .call("alloc", "size", value!(64))
+        .build();
+
+    let engine = add_slices!(Engine::builder().add_layer(layer), base, derived)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(
+        results.get_typed::<i64>("base", "alloc", "size").unwrap(),
+        1
+    );
+    assert_eq!(
+        results
+            .get_typed::<i64>("derived", "alloc", "size")
+            .unwrap(),
+        64
+    );
+    assert_eq!(
+        results
+            .get_typed::<String>("derived", "alloc", "label")
+            .unwrap(),
+        "same"
+    );
+}
+
+#[test]
+fn map_over_names_one_slice_per_item() {
+    let layer = Layer::builder("alloc")
+        .method("size")
+        .args::<Value>()
+        .bind(|args, _ctx| Ok(args.clone()))
+        .build();
+
+    let mut slices = Slice::map_over("run", 10..15, |_, size| {
+        Slice::builder("").layer("alloc", |m| m.call("size", value!(size)))
+    });
+
+    let names: Vec<_> = slices.iter().map(|s| s.name.clone()).collect();
+    assert_eq!(names, ["run_0", "run_1", "run_2", "run_3", "run_4"]);
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slices(&mut slices)
+        .build()
+        .unwrap();
+    let results = engine.run(RunFlags::SILENT);
+
+    for i in 0..5 {
+        let size = results
+            .get_typed::<i64>(&format!("run_{}", i), "alloc", "size")
+            .unwrap();
+        assert_eq!(size, 10 + i);
+    }
+}
+
+#[test]
+fn run_with_meta_reports_the_run_configuration() {
+    let layer = quick_layer!("l1", "work", Value, |_args, _ctx| Ok(value!(null)));
+
+    let mut builder = Engine::builder().add_layer(layer).config(
+        EngineConfig::new()
+            .num_threads(3)
+            .batch_size(500)
+            .chunk_size(10),
+    );
+    for i in 0..5 {
+        builder = builder.add_slice(
+            Slice::builder(format!("s{}", i))
+                .layer("l1", |m| m.call_default("work"))
+                .build(),
+        );
+    }
+    let engine = builder.build().unwrap();
+
+    let (results, metadata) = engine.run_with_meta(RunFlags::SILENT);
+
+    assert!(results.is_all_success());
+    assert_eq!(metadata.batch_size, Some(500));
+    assert_eq!(metadata.chunk_size, 10);
+    assert_eq!(metadata.num_threads, 3);
+    assert!(metadata.total_duration > Duration::ZERO);
+}
+
+#[test]
+fn layers_without_calls_are_satisfied_immediately() {
+    let audit = quick_layer!("audit", "log", Value, |_args, _ctx| Ok(value!(null)));
+    let load = quick_layer!("load", "rows", Value, |_args, _ctx| Ok(value!(3)));
+    let report = quick_layer!("report", "count", Value, |_args, _ctx| Ok(value!(3)));
+
+    // "audit" is declared but calls nothing, while "report" waits on it
+    let engine = Engine::builder()
+        .add_layer(audit)
+        .add_layer(load)
+        .add_layer(report)
+        .dependency("report", "audit")
+        .add_slice(
+            Slice::builder("s")
+                .layer("audit", |m| m)
+                .layer("load", |m| m.call_default("rows"))
+                .layer("report", |m| m.call_default("count"))
+                .build(),
+        )
+        .add_slice(Slice::builder("only_empty").layer("audit", |m| m).build())
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert!(results.is_all_success(), "{}", results.summary());
+    let s = results["s"].as_ref().unwrap();
+    assert_eq!(s.method_results.len(), 2);
+    assert!(
+        results["only_empty"]
+            .as_ref()
+            .unwrap()
+            .method_results
+            .is_empty()
+    );
+}
@@ -77,3 +77,78 @@ fn error_source_chain() {
     let source = std::error::Error::source(&wrapped);
     assert!(source.is_some());
 }
+
+#[test]
+fn io_errors_convert_to_execution_errors() {
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+    let err = Error::from(io);
+    assert!(matches!(err, Error::ExecutionError(_)));
+    assert_eq!(err.message(), "no such file");
+}
+
+#[test]
+fn parse_errors_convert_to_execution_errors() {
+    fn parse(s: &str) -> Result<i64> {
+        Ok(s.parse::<i64>()?)
+    }
+
+    let err = parse("abc").unwrap_err();
+    assert!(matches!(err, Error::ExecutionError(_)));
+    assert_eq!(err.message(), "invalid digit found in string");
+
+    let err = Error::from("x".parse::<f64>().unwrap_err());
+    assert!(matches!(err, Error::ExecutionError(_)));
+    assert_eq!(err.message(), "invalid float literal");
+}
+
+#[derive(Debug)]
+struct UpstreamError {
+    status: u16,
+}
+
+impl std::fmt::Display for UpstreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "upstream returned {}", self.status)
+    }
+}
+
+impl std::error::Error for UpstreamError {}
+
+#[test]
+fn wrapped_errors_keep_a_downcastable_source() {
+    let err = Error::wrap("fetch failed", UpstreamError { status: 503 });
+    assert_eq!(err.to_string(), "fetch failed");
+    assert_eq!(err.message(), "fetch failed: upstream returned 503");
+
+    let source = std::error::Error::source(&err).unwrap();
+    let upstream = source.downcast_ref::<UpstreamError>().unwrap();
+    assert_eq!(upstream.status, 503);
+}
+
+#[test]
+fn wrapped_errors_survive_method_context() {
+    let layer = quick_layer!("net", "fetch", Value, |_args, _ctx| {
+        Err(Error::wrap("fetch failed", UpstreamError { status: 404 }))
+    });
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("s")
+                .layer("net", |m| m.call_default("fetch"))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let err = results.first_error().unwrap();
+    assert!(err.is_execution_error());
+
+    let root = err.root_cause();
+    assert!(matches!(root, Error::Wrapped { .. }));
+    let upstream = std::error::Error::source(root)
+        .and_then(|source| source.downcast_ref::<UpstreamError>())
+        .unwrap();
+    assert_eq!(upstream.status, 404);
+}
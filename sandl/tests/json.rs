@@ -0,0 +1,44 @@
+#![cfg(feature = "serde_json")]
+
+use sandl::*;
+
+#[test]
+fn run_results_to_json() {
+    let layer = Layer::builder("math")
+        .method("double")
+        .args::<i64>()
+        .bind(|x, _ctx| Ok(value!(x * 2)))
+        .method("fail")
+        .args::<Value>()
+        .bind(|_args, _ctx| Err(execution_error!("nope")))
+        .build();
+
+    let s1 = Slice::builder("s1")
+        .layer("math", |m| m.call("double", 21i64))
+        .build();
+    let s2 = Slice::builder("s2")
+        .layer("math", |m| m.call("fail", value!({ "why": "test" })))
+        .build();
+
+    let engine = add_slices!(Engine::builder().add_layer(layer), s1, s2)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let json = results.to_json();
+
+    let s1 = &json["s1"];
+    assert!(s1["duration_ms"].is_number());
+    assert_eq!(s1["methods"]["math.double"], serde_json::json!(42));
+
+    let failure = &json["s2"]["methods"]["math.fail"];
+    assert_eq!(failure["error"], "nope");
+    assert_eq!(failure["context"]["slice"], "s2");
+    assert_eq!(failure["context"]["layer"], "math");
+    assert_eq!(failure["context"]["method"], "fail");
+    assert_eq!(failure["context"]["args"]["why"], "test");
+
+    let pretty = results.to_json_string_pretty();
+    let reparsed: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+    assert_eq!(reparsed, json);
+}
@@ -23,10 +23,14 @@ fn observer_callbacks() {
     });
 
     observer.emit(EngineEvent::SliceStart {
+        run_id: "run".to_string(),
         slice: "s1".to_string(),
+        slice_id: "s1".to_string(),
     });
     observer.emit(EngineEvent::MethodStart {
+        run_id: "run".to_string(),
         slice: "s1".to_string(),
+        slice_id: "s1".to_string(),
         layer: "l1".to_string(),
         method: "m1".to_string(),
     });
@@ -190,3 +194,420 @@ fn observer_multiple_slices() {
 
     assert_eq!(count.load(Ordering::SeqCst), 2);
 }
+
+#[test]
+fn observer_slice_failed() {
+    let a = quick_layer!("a", "work", Value, |_args, _ctx| { Ok(value!({})) });
+    let b = quick_layer!("b", "work", Value, |_args, _ctx| { Ok(value!({})) });
+
+    // "b" depends on "a", but the slice never calls "a"
+    let slice = Slice::builder("test")
+        .layer("b", |m| m.call_default("work"))
+        .build();
+
+    let failures = Arc::new(Mutex::new(Vec::new()));
+    let f = failures.clone();
+
+    let engine = Engine::builder()
+        .add_layer(a)
+        .add_layer(b)
+        .dependency("b", "a")
+        .add_slice(slice)
+        .observe(move |observer| {
+            observer.on_slice_failed(move |slice, error| {
+                f.lock()
+                    .unwrap()
+                    .push((slice.to_string(), error.to_string()));
+            });
+        })
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    assert!(results.get("test").unwrap().is_err());
+
+    let failures = failures.lock().unwrap();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, "test");
+}
+
+#[test]
+fn observer_slice_failed_when_cancelled_between_waves() {
+    let token = CancellationToken::new();
+    let canceller = token.clone();
+
+    let a = quick_layer!("a", "work", Value, move |_args, _ctx| {
+        canceller.cancel();
+        Ok(value!({}))
+    });
+    let b = quick_layer!("b", "work", Value, |_args, _ctx| { Ok(value!({})) });
+
+    let slice = Slice::builder("test")
+        .layer("a", |m| m.call_default("work"))
+        .layer("b", |m| m.call_default("work"))
+        .build();
+
+    let failures = Arc::new(Mutex::new(Vec::new()));
+    let f = failures.clone();
+
+    let engine = Engine::builder()
+        .add_layer(a)
+        .add_layer(b)
+        .dependency("b", "a")
+        .add_slice(slice)
+        .observe(move |observer| {
+            observer.on_slice_failed(move |slice, error| {
+                f.lock()
+                    .unwrap()
+                    .push((slice.to_string(), error.to_string()));
+            });
+        })
+        .build()
+        .unwrap();
+
+    let results = engine.run_with_cancel(token, RunFlags::SILENT);
+    assert!(matches!(
+        results.get("test").unwrap(),
+        Err(Error::Cancelled)
+    ));
+
+    let failures = failures.lock().unwrap();
+    assert_eq!(
+        *failures,
+        vec![("test".to_string(), Error::Cancelled.to_string())]
+    );
+}
+
+#[test]
+fn observer_events_share_run_id() {
+    let layer = quick_layer!("layer", "work", Value, |_args, _ctx| { Ok(value!({})) });
+
+    let s1 = Slice::builder("s1")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+    let s2 = Slice::builder("s2")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+
+    let run_ids = Arc::new(Mutex::new(Vec::new()));
+    let r = run_ids.clone();
+
+    let engine = add_slices!(Engine::builder().add_layer(layer), s1, s2)
+        .observe(move |observer| {
+            observer.on_event(move |event| {
+                r.lock().unwrap().push(event.run_id().to_string());
+            });
+        })
+        .build()
+        .unwrap();
+
+    engine.run(RunFlags::SILENT);
+    let first_run: Vec<String> = run_ids.lock().unwrap().drain(..).collect();
+
+    engine.run(RunFlags::SILENT);
+    let second_run: Vec<String> = run_ids.lock().unwrap().drain(..).collect();
+
+    // 2 run events, plus 2 slice, 2 wave and 2 method events per slice
+    assert_eq!(first_run.len(), 14);
+    assert!(first_run.iter().all(|id| *id == first_run[0]));
+    assert!(second_run.iter().all(|id| *id == second_run[0]));
+    assert_ne!(first_run[0], second_run[0]);
+}
+
+#[test]
+fn observer_events_carry_the_slice_id() {
+    let layer = quick_layer!("layer", "work", Value, |_args, _ctx| { Ok(value!({})) });
+
+    let traced = Slice::builder("traced")
+        .id("trace-7f3a")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+    let plain = Slice::builder("plain")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+
+    let ids = Arc::new(Mutex::new(Vec::new()));
+    let i = ids.clone();
+
+    let engine = add_slices!(Engine::builder().add_layer(layer), traced, plain)
+        .observe(move |observer| {
+            observer.on_event(move |event| {
+                if let EngineEvent::MethodStart {
+                    slice, slice_id, ..
+                }
+                | EngineEvent::MethodComplete {
+                    slice, slice_id, ..
+                } = event
+                {
+                    i.lock().unwrap().push((slice.clone(), slice_id.clone()));
+                }
+            });
+        })
+        .build()
+        .unwrap();
+
+    engine.run(RunFlags::SILENT);
+
+    let mut ids = ids.lock().unwrap().clone();
+    ids.sort();
+    assert_eq!(
+        ids,
+        [
+            ("plain".to_string(), "plain".to_string()),
+            ("plain".to_string(), "plain".to_string()),
+            ("traced".to_string(), "trace-7f3a".to_string()),
+            ("traced".to_string(), "trace-7f3a".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn observer_failure_events_carry_the_slice_id() {
+    let layer = quick_layer!("layer", "work", Value, |_args, _ctx| {
+        Err(Error::ExecutionError("boom".to_string()))
+    });
+
+    let slice = Slice::builder("traced")
+        .id("trace-9c1e")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let e = events.clone();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .config(EngineConfig::new().retry(RetryPolicy::new(2, std::time::Duration::ZERO)))
+        .observe(move |observer| {
+            observer.on_event(move |event| {
+                let kind = match event {
+                    EngineEvent::MethodRetry { .. } => "retry",
+                    EngineEvent::MethodFailed { .. } => "method_failed",
+                    EngineEvent::SliceComplete { .. } => "slice_complete",
+                    EngineEvent::SliceFailed { .. } => "slice_failed",
+                    _ => return,
+                };
+                e.lock()
+                    .unwrap()
+                    .push((kind, event.slice_id().map(str::to_string)));
+            });
+        })
+        .build()
+        .unwrap();
+
+    engine.run(RunFlags::SILENT);
+
+    let events = events.lock().unwrap();
+    assert!(events.iter().any(|(kind, _)| *kind == "retry"));
+    assert!(events.iter().any(|(kind, _)| *kind == "method_failed"));
+    assert!(
+        events
+            .iter()
+            .all(|(_, id)| id.as_deref() == Some("trace-9c1e"))
+    );
+}
+
+#[test]
+fn observer_events_use_configured_run_id() {
+    let layer = quick_layer!("layer", "work", Value, |_args, _ctx| { Ok(value!({})) });
+
+    let slice = Slice::builder("test")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+
+    let run_ids = Arc::new(Mutex::new(Vec::new()));
+    let r = run_ids.clone();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .config(EngineConfig::new().run_id("nightly-42"))
+        .observe(move |observer| {
+            observer.on_event(move |event| {
+                r.lock().unwrap().push(event.run_id().to_string());
+            });
+        })
+        .build()
+        .unwrap();
+
+    engine.run(RunFlags::SILENT);
+
+    let run_ids = run_ids.lock().unwrap();
+    assert!(!run_ids.is_empty());
+    assert!(run_ids.iter().all(|id| id == "nightly-42"));
+}
+
+#[test]
+fn observer_run_start_and_complete() {
+    let layer = quick_layer!("layer", "work", Value, |_args, _ctx| { Ok(value!({})) });
+
+    let s1 = Slice::builder("s1")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+    let s2 = Slice::builder("s2")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+    let s3 = Slice::builder("s3")
+        .layer("layer", |m| m.call_default("work"))
+        .build();
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let e = events.clone();
+
+    let engine = add_slices!(Engine::builder().add_layer(layer), s1, s2, s3)
+        .observe(move |observer| {
+            let start = e.clone();
+            observer.on_run_start(move |total| {
+                start.lock().unwrap().push(format!("run_start:{}", total));
+            });
+            let slice = e.clone();
+            observer.on_slice_complete(move |_, _| {
+                slice.lock().unwrap().push("slice".to_string());
+            });
+            let complete = e.clone();
+            observer.on_run_complete(move |_, succeeded, failed| {
+                complete
+                    .lock()
+                    .unwrap()
+                    .push(format!("run_complete:{}:{}", succeeded, failed));
+            });
+        })
+        .build()
+        .unwrap();
+
+    engine.run(RunFlags::SILENT);
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 5);
+    assert_eq!(events[0], "run_start:3");
+    assert!(events[1..4].iter().all(|e| e == "slice"));
+    assert_eq!(events[4], "run_complete:3:0");
+}
+
+#[test]
+fn observer_wave_events_follow_dependency_order() {
+    let extract = quick_layer!("extract", "load", Value, |_args, _ctx| { Ok(value!({})) });
+    let transform = quick_layer!("transform", "process", Value, |_args, _ctx| {
+        Ok(value!({}))
+    });
+    let load = quick_layer!("load", "save", Value, |_args, _ctx| { Ok(value!({})) });
+
+    let slice = Slice::builder("etl")
+        .layer("extract", |m| m.call_default("load"))
+        .layer("transform", |m| m.call_default("process"))
+        .layer("load", |m| m.call_default("save"))
+        .build();
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let e = events.clone();
+
+    let engine = dependencies!(
+        add_layers!(Engine::builder(), extract, transform, load),
+        "transform" => ["extract"],
+        "load" => ["transform"]
+    )
+    .add_slice(slice)
+    .observe(move |observer| {
+        let start = e.clone();
+        observer.on_wave_start(move |slice, index, size| {
+            start
+                .lock()
+                .unwrap()
+                .push(format!("wave_start:{}:{}:{}", slice, index, size));
+        });
+        let method = e.clone();
+        observer.on_method_start(move |_, layer, _| {
+            method.lock().unwrap().push(layer.to_string());
+        });
+        let complete = e.clone();
+        observer.on_wave_complete(move |slice, index, _| {
+            complete
+                .lock()
+                .unwrap()
+                .push(format!("wave_complete:{}:{}", slice, index));
+        });
+    })
+    .build()
+    .unwrap();
+
+    engine.run(RunFlags::SILENT);
+
+    let events = events.lock().unwrap();
+    assert_eq!(
+        *events,
+        vec![
+            "wave_start:etl:0:1",
+            "extract",
+            "wave_complete:etl:0",
+            "wave_start:etl:1:1",
+            "transform",
+            "wave_complete:etl:1",
+            "wave_start:etl:2:1",
+            "load",
+            "wave_complete:etl:2",
+        ]
+    );
+}
+
+#[test]
+fn observer_clear_drops_callbacks() {
+    let count = Arc::new(AtomicUsize::new(0));
+
+    let mut observer = Observer::new();
+    for _ in 0..3 {
+        let c = count.clone();
+        observer.on_event(move |_| {
+            c.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    assert_eq!(observer.callback_count(), 3);
+
+    observer.clear();
+    assert_eq!(observer.callback_count(), 0);
+
+    observer.emit(EngineEvent::SliceStart {
+        run_id: "run".to_string(),
+        slice: "s".to_string(),
+        slice_id: "s".to_string(),
+    });
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn metrics_observer_counts_events() {
+    let layer = quick_layer!("layer", "work", Value, |args, _ctx| {
+        if args.get("fail").and_then(|v| v.as_bool()).unwrap_or(false) {
+            Err(execution_error!("failed"))
+        } else {
+            Ok(value!({}))
+        }
+    });
+
+    let s1 = Slice::builder("s1")
+        .layer("layer", |m| m.call("work", value!({ "fail": false })))
+        .build();
+    let s2 = Slice::builder("s2")
+        .layer("layer", |m| m.call("work", value!({ "fail": false })))
+        .build();
+    let s3 = Slice::builder("s3")
+        .layer("layer", |m| m.call("work", value!({ "fail": true })))
+        .build();
+
+    let metrics = MetricsObserver::new();
+    let engine = add_slices!(Engine::builder().add_layer(layer), s1, s2, s3)
+        .observer(metrics.observer())
+        .build()
+        .unwrap();
+
+    engine.run(RunFlags::SILENT);
+
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.methods_started, 3);
+    assert_eq!(snapshot.methods_completed, 2);
+    assert_eq!(snapshot.methods_failed, 1);
+    assert_eq!(snapshot.slices_completed, 3);
+
+    let key = ("layer".to_string(), "work".to_string());
+    assert_eq!(snapshot.method_durations[&key].len(), 2);
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "serde")]
+
+use sandl::*;
+
+#[test]
+fn slices_from_config_reads_layers_and_args() {
+    let config = value!({
+        "chunk_b": { "read": { "chunk": { "start": 100, "end": 200 } } },
+        "chunk_a": {
+            "read": { "chunk": { "start": 0, "end": 100 } },
+            "merge": { "fold": (Value::Null) }
+        }
+    });
+
+    let slices = Engine::slices_from_config(&config).unwrap();
+
+    assert_eq!(slices.len(), 2);
+    assert_eq!(slices[0].get_name(), "chunk_a");
+    assert_eq!(slices[1].get_name(), "chunk_b");
+
+    let mut layers = slices[0].get_layer_names().unwrap();
+    layers.sort();
+    assert_eq!(layers, vec!["merge", "read"]);
+    assert_eq!(
+        slices[0].get_method_arg("read", "chunk").unwrap(),
+        &value!({ "start": 0, "end": 100 })
+    );
+    assert!(slices[0].get_method_arg("merge", "fold").unwrap().is_null());
+}
+
+#[test]
+fn slice_from_config_rejects_non_objects() {
+    let err = Slice::from_config("s", &value!({ "read": [1, 2] }))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("Layer 'read' in slice 's'"));
+
+    assert!(Engine::slices_from_config(&value!([1])).is_err());
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn slices_from_config_runs_from_json() {
+    let config: Value =
+        serde_json::from_str(r#"{ "s": { "double": { "run": { "x": 21 } } } }"#).unwrap();
+
+    let layer = quick_layer!("double", "run", Value, |args, _ctx| {
+        Ok(Value::from(args.get("x").unwrap().as_i64().unwrap() * 2))
+    });
+
+    let mut slices = Engine::slices_from_config(&config).unwrap();
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slices(&mut slices)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let slice_results = results.get("s").unwrap().as_ref().unwrap();
+    assert_eq!(
+        slice_results.method_results[&method_key("double", "run")]
+            .as_ref()
+            .unwrap(),
+        &value!(42)
+    );
+}
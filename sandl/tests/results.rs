@@ -0,0 +1,472 @@
+use sandl::*;
+
+#[test]
+fn take_method_moves_result_out() {
+    let layer = Layer::builder("layer")
+        .method("big")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(Value::from(vec![1, 2, 3])))
+        .method("small")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(value!(1)))
+        .build();
+
+    let slice = Slice::builder("s1")
+        .layer("layer", |m| m.call_default("big").call_default("small"))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .build()
+        .unwrap();
+
+    let mut results = engine.run(RunFlags::SILENT);
+    let slice_results = results.get_mut("s1").unwrap().as_mut().unwrap();
+
+    let taken = slice_results.take_method("layer", "big").unwrap().unwrap();
+    assert_eq!(taken.as_array().unwrap().len(), 3);
+
+    assert!(
+        !slice_results
+            .method_results
+            .contains_key(&method_key("layer", "big"))
+    );
+    assert!(slice_results.take_method("layer", "big").is_none());
+    assert_eq!(slice_results.method_results.len(), 1);
+}
+
+#[test]
+fn result_schema_combines_method_outputs() {
+    let layer = Layer::builder("person")
+        .method("name")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(Value::from("Ada")))
+        .method("age")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(Value::from(36)))
+        .method("langs")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(value!(["en", "fr"])))
+        .method("unused")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(Value::Null))
+        .build();
+
+    let slice = Slice::builder("s1")
+        .layer("person", |m| {
+            m.call_default("name")
+                .call_default("age")
+                .call_default("langs")
+                .call_default("unused")
+        })
+        .result_field("full_name", "person", "name")
+        .result_field("years", "person", "age")
+        .result_field("languages", "person", "langs")
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(slice)
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let s1 = results.get("s1").unwrap().as_ref().unwrap();
+
+    assert_eq!(
+        s1.combined,
+        Some(value!({ "full_name": "Ada", "years": 36, "languages": ["en", "fr"] }))
+    );
+    assert_eq!(s1.method_results.len(), 4);
+}
+
+#[test]
+fn result_schema_rejects_uncalled_methods() {
+    let layer = quick_layer!("l1", "work", Value, |_args, _ctx| Ok(Value::Null));
+    let slice = Slice::builder("s1")
+        .layer("l1", |m| m.call_default("work"))
+        .result_field("missing", "l1", "other")
+        .build();
+
+    match Engine::builder().add_layer(layer).add_slice(slice).build() {
+        Err(Error::ConfigError(msg)) => assert!(msg.contains("s1.l1.other"), "{}", msg),
+        Err(e) => panic!("Expected a config error, got {:?}", e),
+        Ok(_) => panic!("Expected a config error"),
+    }
+}
+
+#[test]
+fn get_result_and_get_typed_navigate_run_results() {
+    let layer = Layer::builder("math")
+        .method("double")
+        .args::<Value>()
+        .bind(|args, _ctx| Ok(Value::from(args.get("x").unwrap().as_i64().unwrap() * 2)))
+        .method("fail")
+        .args::<Value>()
+        .bind(|_args, _ctx| Err(execution_error!("nope")))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("s")
+                .layer("math", |m| {
+                    m.call("double", value!({ "x": 21 })).call_default("fail")
+                })
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(
+        results
+            .get_result("s", "math", "double")
+            .unwrap()
+            .as_ref()
+            .unwrap(),
+        &value!(42)
+    );
+    assert_eq!(results.get_typed::<i64>("s", "math", "double").unwrap(), 42);
+
+    assert!(results.get_result("s", "math", "missing").is_none());
+    assert!(results.get_result("nope", "math", "double").is_none());
+    assert!(matches!(
+        results.get_typed::<i64>("s", "math", "missing"),
+        Err(Error::MethodNotFound { .. })
+    ));
+    assert!(results.get_typed::<i64>("nope", "math", "double").is_err());
+    assert!(results.get_typed::<i64>("s", "math", "fail").is_err());
+    assert!(results.get_typed::<String>("s", "math", "double").is_err());
+}
+
+#[test]
+fn by_method_aggregates_across_slices() {
+    let layer = quick_layer!("process", "chunk", Value, |args, _ctx| {
+        match args.get("fail") {
+            Some(_) => Err(execution_error!("bad chunk")),
+            None => Ok(value!(1)),
+        }
+    });
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("ok")
+                .layer("process", |m| m.call("chunk", value!({})))
+                .build(),
+        )
+        .add_slice(
+            Slice::builder("bad")
+                .layer("process", |m| m.call("chunk", value!({ "fail": true })))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let stats = results.by_method();
+
+    assert_eq!(stats.len(), 1);
+    let chunk = &stats[&method_key("process", "chunk")];
+    assert_eq!(chunk.successes, 1);
+    assert_eq!(chunk.failures, 1);
+    assert_eq!(chunk.values, vec![value!(1)]);
+}
+
+#[test]
+fn slowest_methods_ranks_by_duration() {
+    let sleepy = |ms: u64| {
+        move |_args: &Value, _ctx: &Context| {
+            std::thread::sleep(std::time::Duration::from_millis(ms));
+            Ok(Value::Null)
+        }
+    };
+
+    let layer = Layer::builder("l")
+        .method("fast")
+        .args::<Value>()
+        .bind(sleepy(1))
+        .method("medium")
+        .args::<Value>()
+        .bind(sleepy(10))
+        .method("slow")
+        .args::<Value>()
+        .bind(sleepy(30))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("s")
+                .layer("l", |m| {
+                    m.call_default("fast")
+                        .call_default("medium")
+                        .call_default("slow")
+                })
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    let slowest = results.slowest_methods(2);
+    assert_eq!(slowest.len(), 2);
+    assert_eq!(slowest[0].0, method_key("l", "slow"));
+    assert_eq!(slowest[1].0, method_key("l", "medium"));
+    assert!(slowest[0].1 >= std::time::Duration::from_millis(30));
+
+    let p100 = results.method_duration_percentile(100.0).unwrap();
+    assert_eq!(p100, slowest[0].1);
+    assert!(results.method_duration_percentile(0.0).unwrap() < slowest[1].1);
+}
+
+#[test]
+fn method_durations_are_recorded_with_the_observer() {
+    let layer = Layer::builder("l")
+        .method("sleep")
+        .args::<Value>()
+        .bind(|_args, _ctx| {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            Ok(value!(null))
+        })
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("s")
+                .layer("l", |m| m.call_default("sleep"))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let slice = results.get("s").unwrap().as_ref().unwrap();
+    assert!(slice.method_duration("l", "sleep").unwrap() >= std::time::Duration::from_millis(50));
+
+    let results = engine.run(RunFlags::SILENT_NO_OBSERVER);
+    let slice = results.get("s").unwrap().as_ref().unwrap();
+    assert_eq!(slice.method_duration("l", "sleep"), None);
+}
+
+#[test]
+fn method_counts_split_into_success_failure_and_skipped() {
+    let layer = Layer::builder("l")
+        .method("ok")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(value!(1)))
+        .method("fails")
+        .args::<Value>()
+        .bind(|_args, _ctx| Err(execution_error!("nope")))
+        .method("gated")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(value!(2)))
+        .build();
+
+    let slices: Vec<Slice> = (0..2)
+        .map(|i| {
+            Slice::builder(format!("s{}", i))
+                .layer("l", |m| {
+                    m.call_default("ok").call_default("fails").call_if(
+                        "gated",
+                        Value::Null,
+                        |_ctx| false,
+                    )
+                })
+                .build()
+        })
+        .collect();
+
+    let engine = slices
+        .into_iter()
+        .fold(Engine::builder().add_layer(layer), |builder, slice| {
+            builder.add_slice(slice)
+        })
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(results.total_methods(), 6);
+    assert_eq!(results.successful_methods(), 2);
+    assert_eq!(results.failed_methods(), 2);
+    assert_eq!(results.skipped_methods(), 2);
+    assert_eq!(
+        results.successful_methods() + results.failed_methods() + results.skipped_methods(),
+        results.total_methods()
+    );
+    assert!(!results.is_all_success());
+    assert!(
+        results
+            .summary()
+            .ends_with("Methods: 2/6 succeeded, 2 skipped")
+    );
+}
+
+#[test]
+fn into_result_is_ok_when_everything_succeeds() {
+    let layer = Layer::builder("math")
+        .method("double")
+        .args::<i64>()
+        .bind(|&x, _ctx| Ok(value!(x * 2)))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("s")
+                .layer("math", |m| m.call("double", 21))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert!(results.first_error().is_none());
+    assert!(results.into_result().is_ok());
+}
+
+#[test]
+fn into_result_returns_the_first_failure() {
+    let layer = Layer::builder("math")
+        .method("double")
+        .args::<i64>()
+        .bind(|&x, _ctx| Ok(value!(x * 2)))
+        .method("fail")
+        .args::<Value>()
+        .bind(|_args, _ctx| Err(execution_error!("nope")))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("ok")
+                .layer("math", |m| m.call("double", 1))
+                .build(),
+        )
+        .add_slice(
+            Slice::builder("bad")
+                .layer("math", |m| m.call("double", 2).call_default("fail"))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    assert_eq!(results.first_error().unwrap().message(), "nope");
+
+    let err = results.into_result().unwrap_err();
+    assert_eq!(err.message(), "nope");
+    let (slice, layer, method, _) = err.execution_context().unwrap();
+    assert_eq!((slice, layer, method), ("bad", "math", "fail"));
+}
+
+#[test]
+fn csv_export_has_one_row_per_slice_and_method() {
+    let layer = Layer::builder("math")
+        .method("double")
+        .args::<i64>()
+        .bind(|&x, _ctx| Ok(value!(x * 2)))
+        .method("fail")
+        .args::<Value>()
+        .bind(|_args, _ctx| Err(execution_error!("nope")))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("a, quoted")
+                .layer("math", |m| m.call("double", 1))
+                .build(),
+        )
+        .add_slice(
+            Slice::builder("b")
+                .layer("math", |m| m.call("double", 2).call_default("fail"))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+
+    let csv = results.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "slice,duration_ms,methods_total,methods_failed");
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("\"a, quoted\","), "{}", lines[1]);
+    assert!(lines[1].ends_with(",1,0"), "{}", lines[1]);
+    assert!(lines[2].starts_with("b,"), "{}", lines[2]);
+    assert!(lines[2].ends_with(",2,1"), "{}", lines[2]);
+
+    let csv = results.to_method_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "slice,layer,method,status,duration_ms");
+    assert_eq!(lines.len(), 4);
+    assert!(lines[1].starts_with("\"a, quoted\",math,double,ok,"));
+    assert!(lines[2].starts_with("b,math,double,ok,"));
+    assert!(lines[3].starts_with("b,math,fail,failed,"));
+}
+
+#[derive(Args, Debug, PartialEq)]
+struct Summary {
+    label: String,
+    total: i64,
+}
+
+#[test]
+fn typed_result_returns_the_concrete_value() {
+    let layer = Layer::builder("report")
+        .method("summarize")
+        .args::<i64>()
+        .bind_typed(|&total, _ctx| {
+            Ok(TypedResult(Summary {
+                label: "sum".to_string(),
+                total,
+            }))
+        })
+        .method("plain")
+        .args::<Value>()
+        .bind(|_args, _ctx| Ok(value!(1)))
+        .build();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("s")
+                .layer("report", |m| m.call("summarize", 42).call_default("plain"))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let results = engine.run(RunFlags::SILENT);
+    let slice = results["s"].as_ref().unwrap();
+
+    assert_eq!(
+        slice.typed_result::<Summary>("report", "summarize"),
+        Some(&Summary {
+            label: "sum".to_string(),
+            total: 42,
+        })
+    );
+    assert!(slice.typed_result::<i64>("report", "summarize").is_none());
+    assert!(slice.typed_result::<Value>("report", "plain").is_none());
+
+    // The Value form is still the method's regular result
+    assert_eq!(
+        results
+            .get_result("s", "report", "summarize")
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .get("total"),
+        Some(&value!(42))
+    );
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "serde")]
+
+use sandl::*;
+use std::collections::HashMap;
+
+fn nested() -> Value {
+    let mut inner = HashMap::new();
+    inner.insert(
+        "big".to_string(),
+        Value::Number(Number::UnsignedInt(u64::MAX)),
+    );
+    inner.insert("size".to_string(), Value::Number(Number::Size(7)));
+    inner.insert("none".to_string(), Value::Null);
+
+    value!({
+        "name": "station",
+        "min": 1.5,
+        "count": 12,
+        "ok": true,
+        "tags": ["a", "b"],
+        "inner": (Value::Object(inner))
+    })
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn serde_json_round_trip() {
+    let value = nested();
+
+    let json = serde_json::to_string(&value).unwrap();
+    assert!(json.starts_with("{\"count\":12,"));
+
+    let back: Value = serde_json::from_str(&json).unwrap();
+    let inner = back.get("inner").unwrap();
+    assert_eq!(
+        inner.get("big"),
+        Some(&Value::Number(Number::UnsignedInt(u64::MAX)))
+    );
+    // Plain formats don't carry the number variant, so `Size` comes back as `Int`
+    assert_eq!(inner.get("size"), Some(&value!(7i64)));
+    assert_eq!(back.get("min"), Some(&value!(1.5)));
+    assert_eq!(back.get("tags"), value.get("tags"));
+}
+
+#[test]
+fn bincode_round_trip_keeps_variants() {
+    let value = nested();
+
+    let bytes = bincode::serialize(&value).unwrap();
+    let back: Value = bincode::deserialize(&bytes).unwrap();
+
+    assert_eq!(back, value);
+}
+
+#[test]
+fn value_embeds_in_user_structs() {
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct Job {
+        id: u32,
+        args: Value,
+    }
+
+    let job = Job {
+        id: 3,
+        args: nested(),
+    };
+
+    let bytes = bincode::serialize(&job).unwrap();
+    assert_eq!(bincode::deserialize::<Job>(&bytes).unwrap(), job);
+}
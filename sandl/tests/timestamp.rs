@@ -0,0 +1,59 @@
+#![cfg(feature = "timestamp")]
+
+use sandl::*;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[test]
+fn timestamp_typed_accessors() {
+    let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+    let v = Value::from(time);
+
+    assert_eq!(v, Value::Timestamp(1_700_000_000_123));
+    assert_eq!(v.as_timestamp(), Some(time));
+    assert_eq!(SystemTime::from_value(&v).unwrap(), time);
+    assert_eq!(time.to_value(), v);
+
+    let before_epoch = UNIX_EPOCH - Duration::from_millis(1500);
+    assert_eq!(Value::from(before_epoch).as_timestamp_millis(), Some(-1500));
+    assert_eq!(Value::from(before_epoch).as_timestamp(), Some(before_epoch));
+
+    assert!(Value::from("not a date").as_timestamp().is_none());
+    assert!(Value::from(5).as_timestamp().is_none());
+}
+
+#[test]
+fn timestamp_displays_as_iso8601() {
+    assert_eq!(
+        Value::Timestamp(1_700_000_000_123).to_string(),
+        "\"2023-11-14T22:13:20.123Z\""
+    );
+    assert_eq!(
+        Value::Timestamp(0).to_string(),
+        "\"1970-01-01T00:00:00.000Z\""
+    );
+    assert_eq!(
+        Value::Timestamp(-1).to_string(),
+        "\"1969-12-31T23:59:59.999Z\""
+    );
+    assert_eq!(
+        Value::from("2024-02-29T12:00:00Z").as_timestamp_millis(),
+        Some(1_709_208_000_000)
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn timestamp_round_trips_through_json() {
+    let time = UNIX_EPOCH + Duration::from_millis(951_782_400_000);
+    let v = value!({ "at": (Value::from(time)) });
+
+    let json = serde_json::Value::from(v);
+    assert_eq!(
+        json,
+        serde_json::json!({ "at": "2000-02-29T00:00:00.000Z" })
+    );
+
+    let text = serde_json::to_string(&json).unwrap();
+    let back = Value::from(serde_json::from_str::<serde_json::Value>(&text).unwrap());
+    assert_eq!(back.get("at").unwrap().as_timestamp(), Some(time));
+}
@@ -0,0 +1,120 @@
+use sandl::tracker::ProgressTracker;
+use sandl::*;
+
+fn timed_results() -> RunResults {
+    let layer = quick_layer!("layer", "work", Value, |_args, _ctx| { Ok(value!({})) });
+
+    let mut slices: Vec<Slice> = (0..10)
+        .map(|i| {
+            Slice::builder(format!("s{}", i))
+                .layer("layer", |m| m.call_default("work"))
+                .build()
+        })
+        .collect();
+
+    let engine = Engine::builder()
+        .add_layer(layer)
+        .add_slices(&mut slices)
+        .build()
+        .unwrap();
+
+    engine.run(RunFlags::SILENT)
+}
+
+#[test]
+fn summary_includes_percentiles_when_verbose() {
+    let results = timed_results();
+
+    let tracker = ProgressTracker::new(results.len()).verbose_summary(true);
+    let summary = tracker.summary_text(&results);
+
+    assert!(summary.contains("Percentiles: "));
+    assert!(summary.contains("p50: "));
+    assert!(summary.contains("p90: "));
+    assert!(summary.contains("p99: "));
+}
+
+#[test]
+fn summary_omits_percentiles_by_default() {
+    let results = timed_results();
+
+    let tracker = ProgressTracker::new(results.len());
+    let summary = tracker.summary_text(&results);
+
+    assert!(summary.contains("Slices: 10/10 succeeded"));
+    assert!(!summary.contains("Percentiles: "));
+}
+
+#[test]
+fn slice_duration_percentiles_are_ordered() {
+    let results = timed_results();
+
+    let p50 = results.slice_duration_percentile(50.0).unwrap();
+    let p99 = results.slice_duration_percentile(99.0).unwrap();
+
+    assert!(p50 <= p99);
+    assert_eq!(p99, results.max_slice_duration().unwrap());
+    assert_eq!(
+        results.slice_duration_percentile(0.0).unwrap(),
+        results.min_slice_duration().unwrap()
+    );
+}
+
+#[derive(Clone, Default)]
+struct SharedBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn progress_writer_receives_progress_and_summary() {
+    let buffer = SharedBuffer::default();
+    let layer = quick_layer!("layer", "work", Value, |_args, _ctx| { Ok(value!({})) });
+
+    let engine = Engine::builder()
+        .config(
+            EngineConfig::new()
+                .progress_writer(Box::new(buffer.clone()))
+                .progress_interval(std::time::Duration::ZERO),
+        )
+        .add_layer(layer)
+        .add_slice(
+            Slice::builder("s")
+                .layer("layer", |m| m.call_default("work"))
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    engine.run(RunFlags::TRACKED);
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("Starting execution of 1 slices"));
+    assert!(output.contains("Progress: [1/1] 100%"));
+    assert!(output.contains("ETA: 00:00:00"));
+    assert!(output.contains("Total: "));
+}
+
+#[test]
+fn estimate_eta_extrapolates_from_progress() {
+    use sandl::tracker::estimate_eta;
+    use std::time::Duration;
+
+    assert_eq!(estimate_eta(0, 10, Duration::from_secs(5)), None);
+    assert_eq!(
+        estimate_eta(5, 10, Duration::from_secs(30)),
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(
+        estimate_eta(10, 10, Duration::from_secs(30)),
+        Some(Duration::ZERO)
+    );
+}
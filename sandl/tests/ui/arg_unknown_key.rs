@@ -0,0 +1,9 @@
+use sandl::*;
+
+#[derive(Args)]
+struct BadArgs {
+    #[arg(defualt = 1)]
+    retries: i64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use sandl::*;
+
+#[derive(Args)]
+enum Mode {
+    Fast,
+    Slow,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use sandl::*;
+
+#[derive(Args)]
+struct Pair(i64, i64);
+
+fn main() {}
@@ -30,3 +30,350 @@ fn to_value_array() {
     let v = arr.to_value();
     assert_eq!(v.as_array().unwrap().len(), 3);
 }
+
+#[test]
+fn tuple_round_trip() {
+    let tuple = ("chunk".to_string(), 42i64, true);
+    let v = tuple.to_value();
+    assert_eq!(v, value!(["chunk", 42, true]));
+
+    let back: (String, i64, bool) = FromValue::from_value(&v).unwrap();
+    assert_eq!(back, tuple);
+}
+
+#[test]
+fn tuple_length_mismatch() {
+    let v = value!(["chunk", 42]);
+    let err = <(String, i64, bool)>::from_value(&v).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Configuration error: Expected array of length 3, got 2"
+    );
+}
+
+#[test]
+fn as_i64_and_as_f64_accept_unsigned_numbers() {
+    let size = Value::from(7usize);
+    assert_eq!(size.as_i64(), Some(7));
+    assert_eq!(size.as_f64(), Some(7.0));
+
+    let unsigned = Value::from(42u64);
+    assert_eq!(unsigned.as_i64(), Some(42));
+    assert_eq!(unsigned.as_f64(), Some(42.0));
+
+    assert_eq!(Value::from(u64::MAX).as_i64(), None);
+}
+
+#[test]
+fn as_integer_is_lossless_and_rejects_floats() {
+    assert_eq!(Value::from(-3i64).as_integer(), Some(-3));
+    assert_eq!(Value::from(7usize).as_integer(), Some(7));
+    assert_eq!(Value::from(u64::MAX).as_integer(), Some(u64::MAX as i128));
+    assert_eq!(Value::from(2.0).as_integer(), None);
+    assert_eq!(Value::from("2").as_integer(), None);
+
+    for value in [
+        Value::from(-3i64),
+        Value::from(7usize),
+        Value::from(42u64),
+        Value::from(2.5),
+    ] {
+        assert!(value.is_number());
+        assert!(value.as_f64().is_some());
+    }
+    assert!(!Value::from("2").is_number());
+    assert!(!Value::Null.is_number());
+}
+
+#[test]
+fn as_number_and_as_integer_cover_every_numeric_variant() {
+    let cases = [
+        (Value::Number(Number::Int(-3)), Some(-3.0), Some(-3)),
+        (Value::Number(Number::UnsignedInt(42)), Some(42.0), Some(42)),
+        (Value::Number(Number::Size(7)), Some(7.0), Some(7)),
+        (Value::Number(Number::Float(2.5)), Some(2.5), None),
+        (Value::Number(Number::Float(2.0)), Some(2.0), None),
+        (Value::from("2"), None, None),
+        (Value::Null, None, None),
+    ];
+
+    for (value, number, integer) in cases {
+        assert_eq!(value.as_number(), number, "{:?}", value);
+        assert_eq!(value.as_number(), value.as_f64(), "{:?}", value);
+        assert_eq!(value.as_integer(), integer, "{:?}", value);
+    }
+}
+
+#[test]
+fn numbers_compare_across_variants() {
+    use std::collections::HashSet;
+
+    assert_eq!(Value::from(5i64), Value::from(5usize));
+    assert_eq!(Value::from(5u64), Value::from(5.0));
+    assert_eq!(value!({ "chunk": 3 }), value!({ "chunk": (3usize) }));
+
+    assert_ne!(Value::from(5i64), Value::from(6usize));
+    assert_ne!(Value::from(5i64), Value::from(5.5));
+    assert_ne!(Value::from(-1i64), Value::from(u64::MAX));
+
+    let set: HashSet<Value> = [Value::from(5i64), Value::from(5usize), Value::from(5.0)].into();
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn coerce_numbers_to_float() {
+    let sizes = Value::Array(vec![Value::from(1usize), Value::from(2u64)]);
+    let mut v = value!({
+        "count": 3,
+        "ratio": 0.5,
+        "sizes": sizes,
+        "label": "mixed"
+    });
+
+    v.coerce_numbers_to(NumberKind::Float).unwrap();
+
+    assert_eq!(v.get("count"), Some(&Value::Number(Number::Float(3.0))));
+    assert_eq!(v.get("ratio"), Some(&Value::Number(Number::Float(0.5))));
+    assert_eq!(
+        v.get("sizes"),
+        Some(&Value::Array(vec![
+            Value::Number(Number::Float(1.0)),
+            Value::Number(Number::Float(2.0)),
+        ]))
+    );
+    assert_eq!(v.get("label").unwrap().as_str(), Some("mixed"));
+}
+
+#[test]
+fn coerce_numbers_to_int() {
+    let mut v = Value::Array(vec![Value::from(2.0), Value::from(7), Value::from(9usize)]);
+
+    v.coerce_numbers_to(NumberKind::Int).unwrap();
+
+    assert_eq!(
+        v,
+        Value::Array(vec![
+            Value::Number(Number::Int(2)),
+            Value::Number(Number::Int(7)),
+            Value::Number(Number::Int(9)),
+        ])
+    );
+}
+
+#[test]
+fn coerce_numbers_rejects_lossy_conversion() {
+    let mut v = value!({ "whole": 2.0, "fraction": 2.5 });
+    let before = v.clone();
+
+    let result = v.coerce_numbers_to(NumberKind::Int);
+
+    assert!(matches!(result, Err(Error::ConfigError(_))));
+    assert_eq!(v, before);
+
+    let mut negative = Value::from(-1i64);
+    assert!(negative.coerce_numbers_to(NumberKind::UnsignedInt).is_err());
+}
+
+#[test]
+fn deep_merge_nested_objects() {
+    let mut base = value!({ "db": { "host": "x", "port": 5432 }, "debug": false });
+    base.deep_merge(&value!({ "db": { "port": 5433 }, "extra": 1 }));
+
+    assert_eq!(
+        base,
+        value!({ "db": { "host": "x", "port": 5433 }, "debug": false, "extra": 1 })
+    );
+}
+
+#[test]
+fn deep_merge_replaces_arrays() {
+    let mut base = value!({ "tags": ["a", "b", "c"] });
+    base.deep_merge(&value!({ "tags": ["d"] }));
+
+    assert_eq!(base, value!({ "tags": ["d"] }));
+}
+
+#[test]
+fn deep_merge_scalar_replaces_object() {
+    let mut base = value!({ "db": { "host": "x" } });
+    base.deep_merge(&value!({ "db": "sqlite://memory" }));
+    assert_eq!(base, value!({ "db": "sqlite://memory" }));
+
+    let mut scalar = Value::from(1);
+    scalar.deep_merge(&value!({ "a": 1 }));
+    assert_eq!(scalar, value!({ "a": 1 }));
+}
+
+#[test]
+fn get_path_nested_objects() {
+    let v = value!({ "config": { "db": { "port": 5432 } } });
+
+    assert_eq!(v.get_path("config/db/port").unwrap().as_i64(), Some(5432));
+    assert_eq!(v.get_path("config.db.port").unwrap().as_i64(), Some(5432));
+    assert_eq!(v.get_path("/config/db").unwrap(), &value!({ "port": 5432 }));
+}
+
+#[test]
+fn get_path_array_indexing() {
+    let mut v = value!({ "stats": [{ "min": 1, "max": 9 }, { "min": 2, "max": 8 }] });
+
+    assert_eq!(v.get_path("stats/0/min").unwrap().as_i64(), Some(1));
+    assert_eq!(v.get_path("stats/1/max").unwrap().as_i64(), Some(8));
+
+    *v.get_path_mut("stats/1/min").unwrap() = Value::from(5);
+    assert_eq!(v.get_path("stats.1.min").unwrap().as_i64(), Some(5));
+}
+
+#[test]
+fn get_path_misses() {
+    let v = value!({ "stats": [{ "min": 1 }], "name": "x" });
+
+    assert!(v.get_path("missing").is_none());
+    assert!(v.get_path("stats/3/min").is_none());
+    assert!(v.get_path("stats/first").is_none());
+    assert!(v.get_path("name/inner").is_none());
+}
+
+#[test]
+fn to_pretty_sorted_is_order_independent() {
+    let mut a = std::collections::HashMap::new();
+    a.insert("zeta".to_string(), Value::from(1));
+    a.insert("alpha".to_string(), value!({ "y": [1, 2.5], "x": null }));
+    a.insert("mid".to_string(), Value::from("text"));
+
+    let mut b = std::collections::HashMap::new();
+    b.insert("mid".to_string(), Value::from("text"));
+    b.insert("alpha".to_string(), value!({ "x": null, "y": [1, 2.5] }));
+    b.insert("zeta".to_string(), Value::from(1));
+
+    let a = Value::Object(a).to_pretty_sorted(2);
+    let b = Value::Object(b).to_pretty_sorted(2);
+
+    assert_eq!(a, b);
+    assert_eq!(
+        a,
+        "{\n  \"alpha\": {\n    \"x\": null,\n    \"y\": [\n      1,\n      2.5\n    ]\n  },\n  \"mid\": \"text\",\n  \"zeta\": 1\n}"
+    );
+    assert_eq!(value!({}).to_pretty_sorted(4), "{}");
+}
+
+#[test]
+fn display_compact_json() {
+    let v = value!({
+        "b": [1, 3.5, true],
+        "d": null,
+        "a": { "nested": "x", "empty": [] },
+        "c": {}
+    });
+
+    assert_eq!(
+        v.to_string(),
+        r#"{"a":{"empty":[],"nested":"x"},"b":[1,3.5,true],"c":{},"d":null}"#
+    );
+    assert_eq!(Value::from(2.0).to_string(), "2.0");
+    assert_eq!(Value::from(7usize).to_string(), "7");
+    assert_eq!(Value::from(-2).to_string(), "-2");
+}
+
+#[test]
+fn display_escapes_strings() {
+    let v = Value::from("say \"hi\"\nand \\ leave\t");
+    assert_eq!(v.to_string(), r#""say \"hi\"\nand \\ leave\t""#);
+
+    let mut map = std::collections::HashMap::new();
+    map.insert("quote\"key".to_string(), Value::from("line\nbreak"));
+    let v = Value::Object(map);
+    assert_eq!(v.to_string(), r#"{"quote\"key":"line\nbreak"}"#);
+}
+
+#[test]
+fn value_hash_ignores_key_order() {
+    use std::collections::{HashMap, HashSet};
+
+    let mut forward = HashMap::new();
+    let mut backward = HashMap::new();
+    for i in 0..16 {
+        forward.insert(format!("k{}", i), Value::from(i));
+    }
+    for i in (0..16).rev() {
+        backward.insert(format!("k{}", i), Value::from(i));
+    }
+
+    let mut set = HashSet::new();
+    set.insert(Value::Object(forward));
+    set.insert(Value::Object(backward));
+    set.insert(value!({ "a": 1, "b": [1.5, 2.5] }));
+    set.insert(value!({ "b": [1.5, 2.5], "a": 1 }));
+    set.insert(value!({ "a": 2 }));
+
+    assert_eq!(set.len(), 3);
+    assert!(set.contains(&value!({ "a": 2 })));
+    assert!(set.contains(&Value::Object(HashMap::from([(
+        "a".to_string(),
+        Value::from(2)
+    )]))));
+}
+
+#[test]
+fn value_hash_folds_negative_zero() {
+    use std::collections::HashSet;
+
+    let set: HashSet<Value> = [Value::from(0.0), Value::from(-0.0)].into_iter().collect();
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn value_dyn_mixes_variable_and_literal_keys() {
+    let station = "Hamburg";
+    let column = String::from("max");
+
+    let value = value_dyn!({
+        (station) => { "min": 1, "max": 9 },
+        (column) => 9,
+        "count" => 2,
+    });
+
+    assert_eq!(value.get("Hamburg"), Some(&value!({ "min": 1, "max": 9 })));
+    assert_eq!(value.get("max"), Some(&value!(9)));
+    assert_eq!(value.get("count"), Some(&value!(2)));
+}
+
+#[test]
+fn value_dyn_accepts_expression_values() {
+    let stations = ["a", "b"];
+    let index = 1;
+
+    let value = value_dyn!({
+        (stations[index]) => index as i64 * 10,
+        (format!("{}_total", stations[0])) => 3 + 4,
+    });
+
+    assert_eq!(value.get("b"), Some(&value!(10)));
+    assert_eq!(value.get("a_total"), Some(&value!(7)));
+}
+
+#[test]
+fn entry_inserts_missing_keys() {
+    let mut stats = value!({});
+
+    stats.entry("count").unwrap().or_insert(value!(1));
+    assert_eq!(stats, value!({ "count": 1 }));
+
+    let mut list = value!([1, 2]);
+    assert!(list.entry("count").is_none());
+}
+
+#[test]
+fn entry_modifies_existing_keys() {
+    let mut stats = value!({ "count": 1 });
+
+    for _ in 0..2 {
+        stats
+            .entry("count")
+            .unwrap()
+            .and_modify(|count| *count = value!(count.as_i64().unwrap() + 1))
+            .or_insert(value!(1));
+    }
+
+    assert_eq!(stats, value!({ "count": 3 }));
+}
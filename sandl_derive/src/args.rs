@@ -1,6 +1,6 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields};
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Field, Fields, LitStr, Type};
 
 pub fn impl_args(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -9,24 +9,79 @@ pub fn impl_args(input: TokenStream) -> TokenStream {
     let fields = match &input.data {
         Data::Struct(data) => match &data.fields {
             Fields::Named(fields) => &fields.named,
-            _ => panic!("Args can only be derived for structs with named fields"),
+            _ => {
+                return syn::Error::new_spanned(
+                    &input,
+                    "Args can only be derived for structs with named fields",
+                )
+                .to_compile_error()
+                .into()
+            }
         },
-        _ => panic!("Args can only be derived for structs"),
+        _ => {
+            return syn::Error::new_spanned(&input, "Args can only be derived for structs")
+                .to_compile_error()
+                .into()
+        }
     };
 
-    let from_value_fields = fields.iter().map(|f| {
+    let mut from_value_fields = Vec::new();
+    let mut to_value_fields = Vec::new();
+    for f in fields {
         let field_name = &f.ident;
-        let field_name_str = field_name.as_ref().unwrap().to_string();
         let field_type = &f.ty;
 
-        quote! {
+        let attrs = match field_attrs(f) {
+            Ok(attrs) => attrs,
+            Err(e) => return e.to_compile_error().into(),
+        };
+
+        let field_name_str = attrs
+            .rename
+            .unwrap_or_else(|| field_name.as_ref().unwrap().to_string());
+        let optional = is_option(field_type);
+
+        // `None` fields are left out rather than written as null
+        to_value_fields.push(if optional {
+            quote! {
+                if let Some(v) = &self.#field_name {
+                    map.insert(#field_name_str.to_string(), sandl::ToValue::to_value(v));
+                }
+            }
+        } else {
+            quote! {
+                map.insert(
+                    #field_name_str.to_string(),
+                    <_ as sandl::ToValue>::to_value(&self.#field_name)
+                );
+            }
+        });
+
+        from_value_fields.push(match attrs.default {
+            // Defaults go through ToValue so a literal like `100` or `"x"` fits any field type
+            Some(default) => quote! {
+                let #field_name = match obj.get(#field_name_str) {
+                    Some(v) => <#field_type as sandl::FromValue>::from_value(v)?,
+                    None => <#field_type as sandl::FromValue>::from_value(
+                        &sandl::ToValue::to_value(&(#default))
+                    )?,
+                };
+            },
+            None if optional => quote! {
+                let #field_name = match obj.get(#field_name_str) {
+                    Some(v) => <#field_type as sandl::FromValue>::from_value(v)?,
+                    None => None,
+                };
+            },
+            None => quote! {
                 let #field_name = obj.get(#field_name_str)
                     .ok_or_else(|| sandl::Error::ConfigError(
                         format!("Missing required argument '{}' in {}", #field_name_str, stringify!(#name))
                     ))?;
                 let #field_name = <#field_type as sandl::FromValue>::from_value(#field_name)?;
-        }
-    });
+            },
+        });
+    }
 
     let field_names = fields.iter().map(|f| f.ident.clone()).collect::<Vec<_>>();
 
@@ -49,12 +104,7 @@ pub fn impl_args(input: TokenStream) -> TokenStream {
         impl sandl::ToValue for #name {
             fn to_value(&self) -> sandl::Value {
                 let mut map = std::collections::HashMap::new();
-                #(
-                    map.insert(
-                        stringify!(#field_names).to_string(),
-                        <_ as sandl::ToValue>::to_value(&self.#field_names)
-                    );
-                )*
+                #(#to_value_fields)*
                 sandl::Value::Object(map)
             }
         }
@@ -62,3 +112,43 @@ pub fn impl_args(input: TokenStream) -> TokenStream {
 
     TokenStream::from(expanded)
 }
+
+// Matches `Option<T>` by its last path segment, so `std::option::Option<T>` works too
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    default: Option<Expr>,
+    rename: Option<String>,
+}
+
+fn field_attrs(field: &Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("arg")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                attrs.default = Some(meta.value()?.parse::<Expr>()?);
+                Ok(())
+            } else if meta.path.is_ident("rename") {
+                attrs.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "unsupported arg attribute, expected `default = ...` or `rename = \"...\"`",
+                ))
+            }
+        })?;
+    }
+
+    Ok(attrs)
+}
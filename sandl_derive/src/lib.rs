@@ -2,7 +2,7 @@ use proc_macro::TokenStream;
 
 mod args;
 
-#[proc_macro_derive(Args)]
+#[proc_macro_derive(Args, attributes(arg))]
 pub fn derive_args(input: TokenStream) -> TokenStream {
     args::impl_args(input)
 }